@@ -0,0 +1,333 @@
+// Command line interface for computing rotation curves without the browser
+use galaxien_rotation::utils::{
+    fit::fit_rotation_curve,
+    galaxy_model::{GalaxyModel, HaloContraction, HaloProfile, HaloTruncation},
+    mass_component::total_velocity,
+    observed::parse_observed,
+};
+use serde_json::json;
+use std::{collections::HashMap, process::exit};
+
+const USAGE: &str = "\
+Usage:
+  galaxien_cli curve [OPTIONS]
+  galaxien_cli fit <FILE> [OPTIONS]
+  galaxien_cli check [OPTIONS]
+
+Commands:
+  curve    Print velocity, mass and density for every radius of the grid
+  fit      Fit the parameters to an observed curve (\"radius velocity [error]\" per line, - for stdin)
+  check    Compare the closed forms with numerical integration of the density functions
+
+Options:
+  --model <FILE>           Start from a model saved as JSON, the options below override it
+  --disk-density <VALUE>   Initial surface density of the disk in kg/m² [default: 1.01]
+  --disk-scale <VALUE>     Scale length of the disk in kpc [default: 4.5]
+  --halo-density <VALUE>   Initial density of the halo in kg/m³ [default: 1.52e-21]
+  --halo-scale <VALUE>     Scale length of the halo in kpc [default: 15.91]
+  --halo <PROFILE>         Density function of the halo, iso or nfw [default: iso]
+  --halo-axis-ratio <Q>    Axis ratio of the halo, below 1 oblate, above 1 prolate [default: 1]
+  --truncation <MODE>      Density of the halo beyond r200, none, virial or exponential [default: none]
+  --contraction <MODE>     Adiabatic contraction of the halo, none, blumenthal or gnedin [default: none]
+  --disk-formula <TEXT>    Custom surface density of the disk in terms of r, rho0 and a, e.g. \"rho0 * exp(-r/a)\"
+  --halo-formula <TEXT>    Custom density of the halo; further names become parameters, set in the model JSON
+  --r-min <VALUE>          First radius of the grid in kpc [default: 0]
+  --r-max <VALUE>          Last radius of the grid in kpc [default: 45.25]
+  --step <VALUE>           Step of the grid in kpc [default: 0.25]
+  --free <SET>             Parameters to fit, all, disk or halo [default: all]
+  --format <FORMAT>        Output format, csv or json [default: csv]; fit prints the model as JSON
+  -h, --help               Print this help";
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    exit(2);
+}
+
+// Split arguments into positional values and --key value options
+fn parse_args(args: &[String]) -> (Vec<String>, HashMap<String, String>) {
+    let mut positional = Vec::new();
+    let mut options = HashMap::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{}", USAGE);
+            exit(0);
+        } else if let Some(key) = arg.strip_prefix("--") {
+            let value = iter
+                .next()
+                .unwrap_or_else(|| fail(&format!("missing value for --{}", key)));
+            options.insert(key.to_string(), value.clone());
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+
+    (positional, options)
+}
+
+fn get_number(options: &HashMap<String, String>, key: &str) -> Option<f64> {
+    options.get(key).map(|value| {
+        value
+            .parse::<f64>()
+            .unwrap_or_else(|_| fail(&format!("invalid number for --{}: {}", key, value)))
+    })
+}
+
+fn read_input(file: &str) -> String {
+    if file == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(file)
+    }
+    .unwrap_or_else(|err| fail(&format!("could not read {}: {}", file, err)))
+}
+
+struct Config {
+    model: GalaxyModel,
+    radii: Vec<f64>,
+    json: bool,
+}
+
+fn get_config(options: &HashMap<String, String>) -> Config {
+    let mut model = match options.get("model") {
+        Some(file) => serde_json::from_str(&read_input(file))
+            .unwrap_or_else(|err| fail(&format!("invalid model in {}: {}", file, err))),
+        None => GalaxyModel::default(),
+    };
+
+    // The profile first, it decides whether the halo is given by its virial values
+    match options.get("halo").map(String::as_str) {
+        None => {}
+        Some("iso") => model.halo.profile = HaloProfile::Isothermal,
+        Some("nfw") => model.halo.profile = HaloProfile::Nfw,
+        Some(other) => fail(&format!("unknown halo profile: {}", other)),
+    }
+
+    for (option, key) in [
+        ("disk-density", "disk.density"),
+        ("disk-scale", "disk.scale"),
+        ("halo-density", "halo.density"),
+        ("halo-scale", "halo.scale"),
+        ("halo-axis-ratio", "halo.axis_ratio"),
+    ] {
+        if let Some(value) = get_number(options, option) {
+            if option == "halo-axis-ratio" && !(value.is_finite() && value > 0.0) {
+                fail(&format!("--{} needs a positive number, got {}", option, value));
+            }
+            if !model.set(key, value) {
+                fail(&format!("--{} can not be set while the halo is given by its virial values", option));
+            }
+        }
+    }
+
+    match options.get("truncation").map(String::as_str) {
+        None => {}
+        Some("none") => model.halo.truncation = HaloTruncation::None,
+        Some("virial") => model.halo.truncation = HaloTruncation::Virial,
+        Some("exponential") => model.halo.truncation = HaloTruncation::Exponential,
+        Some(other) => fail(&format!("unknown halo truncation: {}", other)),
+    }
+
+    match options.get("contraction").map(String::as_str) {
+        None => {}
+        Some("none") => model.halo.contraction = HaloContraction::None,
+        Some("blumenthal") => model.halo.contraction = HaloContraction::Blumenthal,
+        Some("gnedin") => model.halo.contraction = HaloContraction::Gnedin,
+        Some(other) => fail(&format!("unknown halo contraction: {}", other)),
+    }
+
+    for (option, group) in [("disk-formula", "disk"), ("halo-formula", "halo")] {
+        if let Some(formula) = options.get(option) {
+            model
+                .set_formula(group, formula)
+                .unwrap_or_else(|err| fail(&format!("invalid formula for --{}: {}", option, err)));
+        }
+    }
+
+    let json = match options.get("format").map(String::as_str) {
+        None | Some("csv") => false,
+        Some("json") => true,
+        Some(other) => fail(&format!("unknown format: {}", other)),
+    };
+
+    // Same grid as the charts by default
+    let r_min = get_number(options, "r-min").unwrap_or(0.0);
+    let r_max = get_number(options, "r-max").unwrap_or(45.25);
+    let step = get_number(options, "step").unwrap_or(0.25);
+    if step <= 0.0 || r_max < r_min {
+        fail("the grid needs step > 0 and r-max >= r-min");
+    }
+    let count = ((r_max - r_min) / step + 1e-9).floor() as usize + 1;
+    let radii = (0..count).map(|i| r_min + i as f64 * step).collect();
+
+    Config { model, radii, json }
+}
+
+fn print_curve(config: &Config) {
+    let components = config.model.components();
+
+    // Total velocity, then mass and density of every component
+    let mut columns = vec!["radius_kpc".to_string(), "velocity_km_s".to_string()];
+    for component in &components {
+        columns.push(format!("mass_{}_1e10_msun", component.name().to_lowercase()));
+    }
+    for component in &components {
+        columns.push(format!("density_{}", component.name().to_lowercase()));
+    }
+
+    let rows: Vec<Vec<f64>> = config
+        .radii
+        .iter()
+        .map(|&r| {
+            let mut row = vec![r, total_velocity(&components, r)];
+            row.extend(components.iter().map(|component| component.mass(r)));
+            row.extend(components.iter().map(|component| component.density(r)));
+            row
+        })
+        .collect();
+
+    if config.json {
+        let points: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, value)| (column.clone(), json!(value)))
+                    .collect::<serde_json::Map<_, _>>()
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&points).unwrap());
+    } else {
+        println!("{}", columns.join(","));
+        for row in rows {
+            let values: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+            println!("{}", values.join(","));
+        }
+    }
+}
+
+fn check(config: &Config) {
+    let closed = config.model.components();
+    let numerical = config.model.numerical_components();
+
+    let columns = [
+        "radius_kpc",
+        "component",
+        "velocity_km_s",
+        "velocity_numerical_km_s",
+        "mass_1e10_msun",
+        "mass_numerical_1e10_msun",
+        "potential_km2_s2",
+        "potential_numerical_km2_s2",
+    ];
+
+    let mut rows = Vec::new();
+    for &r in &config.radii {
+        for (closed, numerical) in closed.iter().zip(&numerical) {
+            rows.push((
+                r,
+                closed.name().to_lowercase(),
+                [
+                    closed.velocity(r),
+                    numerical.velocity(r),
+                    closed.mass(r),
+                    numerical.mass(r),
+                    closed.potential(r),
+                    numerical.potential(r),
+                ],
+            ));
+        }
+    }
+
+    if config.json {
+        let points: Vec<_> = rows
+            .iter()
+            .map(|(r, name, values)| {
+                let mut point = serde_json::Map::new();
+                point.insert(columns[0].to_string(), json!(r));
+                point.insert(columns[1].to_string(), json!(name));
+                for (column, value) in columns[2..].iter().zip(values) {
+                    point.insert(column.to_string(), json!(value));
+                }
+                point
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&points).unwrap());
+    } else {
+        println!("{}", columns.join(","));
+        for (r, name, values) in rows {
+            let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+            println!("{},{},{}", r, name, values.join(","));
+        }
+    }
+}
+
+fn fit(file: &str, options: &HashMap<String, String>) {
+    let config = get_config(options);
+
+    let observed = parse_observed(&read_input(file)).unwrap_or_else(|err| fail(&format!("{}: {}", file, err)));
+
+    let group = match options.get("free").map(String::as_str) {
+        None | Some("all") => None,
+        Some(group @ ("disk" | "halo")) => Some(group),
+        Some(other) => fail(&format!("unknown parameter set: {}", other)),
+    };
+    let free: Vec<String> = config
+        .model
+        .parameters()
+        .into_iter()
+        .filter(|parameter| group.is_none_or(|group| parameter.group == group))
+        .map(|parameter| parameter.key)
+        .collect();
+
+    let result = fit_rotation_curve(&observed, &config.model, &free);
+
+    if config.json {
+        let output = json!({
+            "model": result.model,
+            "rms": result.rms,
+            "iterations": result.iterations,
+            "points": observed.len(),
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    } else {
+        let parameters = result.model.parameters();
+        let keys: Vec<&str> = parameters.iter().map(|parameter| parameter.key.as_str()).collect();
+        let values: Vec<String> = keys
+            .iter()
+            .map(|key| result.model.get(key).unwrap_or(f64::NAN).to_string())
+            .collect();
+        let profile = match result.model.halo.profile {
+            HaloProfile::Isothermal => "iso",
+            HaloProfile::Nfw => "nfw",
+        };
+
+        println!("{},halo.profile,rms,iterations,points", keys.join(","));
+        println!(
+            "{},{},{},{},{}",
+            values.join(","),
+            profile,
+            result.rms,
+            result.iterations,
+            observed.len()
+        );
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (positional, options) = parse_args(&args);
+
+    match positional.first().map(String::as_str) {
+        Some("curve") if positional.len() == 1 => print_curve(&get_config(&options)),
+        Some("curve") => fail("curve takes no input file"),
+        Some("check") if positional.len() == 1 => check(&get_config(&options)),
+        Some("check") => fail("check takes no input file"),
+        Some("fit") if positional.len() == 2 => fit(&positional[1], &options),
+        Some("fit") => fail("fit needs exactly one input file"),
+        Some(other) => fail(&format!("unknown command: {}", other)),
+        None => fail("missing command"),
+    }
+}
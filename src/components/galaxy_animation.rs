@@ -0,0 +1,130 @@
+use leptos::{html::Canvas, prelude::*};
+use leptos_use::{use_raf_fn, UseRafFnCallbackArgs};
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use crate::{
+    elements::toggle_button::OptionToggle,
+    utils::{
+        galaxy_model::GalaxyModel,
+        mass_component::total_velocity,
+        particles::{seed_particles, Particle},
+        radial_grid::RadialGrid,
+        translation::Translation,
+    },
+};
+
+const PARTICLES: usize = 2000;
+const SEED: u64 = 0x5eed;
+// Simulated time per second of animation
+const MYR_PER_SECOND: f64 = 25.0;
+
+// Draw the particles face-on, the maximum radius fills the smaller side of the canvas
+fn draw(canvas: &HtmlCanvasElement, particles: &[Particle], max_radius: f64) {
+    // Match the resolution to the displayed size
+    let (width, height) = (canvas.client_width().max(1) as u32, canvas.client_height().max(1) as u32);
+    if canvas.width() != width || canvas.height() != height {
+        canvas.set_width(width);
+        canvas.set_height(height);
+    }
+
+    let Some(context) = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+    else {
+        return;
+    };
+
+    let (width, height) = (width as f64, height as f64);
+    context.clear_rect(0.0, 0.0, width, height);
+    let scale = width.min(height) / 2.0 / max_radius;
+
+    for particle in particles {
+        let x = width / 2.0 + particle.radius * particle.angle.cos() * scale;
+        let y = height / 2.0 - particle.radius * particle.angle.sin() * scale;
+        if particle.arm {
+            context.set_fill_style_str("#f0a8ff");
+            context.fill_rect(x - 1.0, y - 1.0, 2.5, 2.5);
+        } else {
+            context.set_fill_style_str("rgba(255, 255, 255, 0.6)");
+            context.fill_rect(x - 0.75, y - 0.75, 1.5, 1.5);
+        }
+    }
+}
+
+#[component]
+pub fn GalaxyAnimation(
+    text: ReadSignal<Translation>,
+    // Move the particles with the velocity of the disk alone
+    disk_only: RwSignal<bool>,
+) -> impl IntoView {
+    // Get animation section of text
+    let animation_text: Memo<HashMap<String, String>> =
+        Memo::new(move |_| text.get().0.get("animation").cloned().unwrap_or_default());
+
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let grid = expect_context::<RwSignal<RadialGrid>>();
+
+    let canvas_ref = NodeRef::<Canvas>::new();
+    let particles = StoredValue::new(Vec::<Particle>::new());
+    // Changed to start again with the same particles
+    let restart = RwSignal::new(());
+
+    // New particles whenever the model, the radius or the velocities change
+    Effect::new(move |_| {
+        restart.track();
+        let disk_only = disk_only.get();
+        let max_radius = grid.with(|grid| grid.max_radius);
+        let seeded = model.with(|model| {
+            let components = model.disk_and_halo();
+            let [disk, _] = &components;
+            let velocity = |r: f64| {
+                if disk_only {
+                    disk.velocity(r)
+                } else {
+                    total_velocity(&components, r)
+                }
+            };
+            seed_particles(PARTICLES, model.disk.scale, max_radius, SEED, |r| disk.mass(r), velocity)
+        });
+        particles.set_value(seeded);
+    });
+
+    use_raf_fn(move |args: UseRafFnCallbackArgs| {
+        let Some(canvas) = canvas_ref.get_untracked() else {
+            return;
+        };
+        let time = args.delta / 1000.0 * MYR_PER_SECOND;
+        particles.update_value(|particles| {
+            for particle in particles.iter_mut() {
+                particle.angle += particle.omega * time;
+            }
+        });
+        let max_radius = grid.with_untracked(|grid| grid.max_radius);
+        particles.with_value(|particles| draw(&canvas, particles, max_radius));
+    });
+
+    let translate = move |key: &'static str| {
+        move || animation_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    view! {
+        <div class="chart chart_primary galaxy-animation">
+            <canvas node_ref=canvas_ref></canvas>
+            <div class="axis-controls">
+                <OptionToggle
+                    selected=disk_only
+                    on_select=move |target| disk_only.set(target)
+                    options=vec![(false, "Disk and halo"), (true, "Disk only")]
+                    text=animation_text
+                />
+                <button class="axis-toggle" on:click=move |_| restart.set(())>
+                    {translate("Restart")}
+                </button>
+                <span>{translate("The highlighted particles start on two spiral arms")}</span>
+            </div>
+        </div>
+    }
+}
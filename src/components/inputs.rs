@@ -1,21 +1,42 @@
-use crate::utils::translation::Translation;
+use crate::{
+    elements::toggle_button::OptionToggle,
+    utils::{
+        galaxy_model::{GalaxyModel, HaloContraction, HaloProfile, HaloTruncation, ModelParameter, REDSHIFT_LIMITS},
+        radial_grid::{RadialGrid, Spacing, MAX_RADIUS_LIMITS, SAMPLES_LIMITS},
+        translation::Translation,
+    },
+};
 use leptos::prelude::*;
+use std::collections::HashMap;
 use wasm_bindgen::JsCast;
 use web_sys::{Event, HtmlInputElement, WheelEvent};
 
 #[derive(Clone)]
 struct SliderConfig {
+    // Key of the parameter in the model
+    key: String,
     min_value: f64,
     max_value: f64,
     step: f64,
     factor: f64,
 }
 
+impl SliderConfig {
+    // Slider moving the parameter in whole steps
+    fn from_parameter(parameter: &ModelParameter) -> Self {
+        Self {
+            key: parameter.key.clone(),
+            min_value: (parameter.min / parameter.step).round(),
+            max_value: (parameter.max / parameter.step).round(),
+            step: 1.0,
+            factor: parameter.step,
+        }
+    }
+}
+
 fn wheel_handle(
-    slider_i: usize,
     config: SliderConfig,
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
-    set_slider_values: WriteSignal<(f64, f64, f64, f64)>,
+    model: RwSignal<GalaxyModel>,
 ) -> impl Fn(WheelEvent) + Clone + 'static {
     move |wheel_ev: WheelEvent| {
         // Stop default behaviour
@@ -57,40 +78,420 @@ fn wheel_handle(
         // Update DOM
         input.set_value(&value.to_string());
 
-        // Get old slider values
-        let old_slider_values = slider_values.get_untracked();
-
-        // Get and change value
-        let new_value = value * config.factor;
-        let new_slider_values = match slider_i {
-            0 => (
-                new_value,
-                old_slider_values.1,
-                old_slider_values.2,
-                old_slider_values.3,
-            ),
-            1 => (
-                old_slider_values.0,
-                new_value,
-                old_slider_values.2,
-                old_slider_values.3,
-            ),
-            2 => (
-                old_slider_values.0,
-                old_slider_values.1,
-                new_value,
-                old_slider_values.3,
-            ),
-            3 => (
-                old_slider_values.0,
-                old_slider_values.1,
-                old_slider_values.2,
-                new_value,
-            ),
-            _ => old_slider_values,
-        };
-
-        set_slider_values(new_slider_values);
+        // Change value in model
+        model.update(|model| {
+            model.set(&config.key, value * config.factor);
+        });
+    }
+}
+
+#[component]
+fn ParameterSlider(
+    parameter: ModelParameter,
+    input_text: Memo<HashMap<String, String>>,
+) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
+    let config = SliderConfig::from_parameter(&parameter);
+    let id = parameter.key.replace('.', "-");
+    let input_id = id.clone();
+    let label_for = id.clone();
+
+    let on_wheel = wheel_handle(config.clone(), model);
+    let on_input = {
+        let config = config.clone();
+        move |ev: Event| {
+            let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+            let raw_value = input.value().parse::<f64>().unwrap_or(config.min_value);
+            model.update(|model| {
+                model.set(&config.key, raw_value * config.factor);
+            });
+        }
+    };
+
+    let key = parameter.key.clone();
+    let value = Memo::new(move |_| model.with(|model| model.get(&key).unwrap_or_default()));
+
+    let label = parameter.label.clone();
+    let exponential = parameter.exponential;
+
+    view! {
+        <div class="input-vertical">
+            <div id=id class="input-range-cont-with-value">
+                <label for=label_for>
+                    {move || {
+                        input_text
+                            .get()
+                            .get(&label)
+                            .cloned()
+                            .unwrap_or(label.clone())
+                    }}
+                </label>
+                <div class="input-small-range-inner-cont">
+                    <input
+                        class="small-range"
+                        id=input_id
+                        type="range"
+                        min=config.min_value.to_string()
+                        max=config.max_value.to_string()
+                        prop:value=move || (value.get() / config.factor).round().to_string()
+                        on:input=on_input
+                        on:wheel=on_wheel
+                    />
+                </div>
+                <span>
+                    {move || {
+                        if exponential {
+                            format!("{:.2e}", value.get())
+                        } else {
+                            format!("{:.2}", value.get())
+                        }
+                    }}
+                    " "
+                    {parameter.unit}
+                </span>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn FormulaInput(
+    // Component of the model, "disk" or "halo"
+    group: &'static str,
+    label: &'static str,
+    placeholder: &'static str,
+    input_text: Memo<HashMap<String, String>>,
+) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
+    // Message of the parser, if the typed formula is invalid
+    let (error, set_error) = signal(None::<String>);
+
+    let formula = Memo::new(move |_| {
+        model.with(|model| {
+            model
+                .custom(group)
+                .map(|custom| custom.formula.clone())
+                .unwrap_or_default()
+        })
+    });
+
+    // Only valid formulas reach the model
+    let on_change = move |ev: Event| {
+        let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+        let mut updated = model.get_untracked();
+        match updated.set_formula(group, &input.value()) {
+            Ok(()) => {
+                set_error.set(None);
+                model.set(updated);
+            }
+            Err(err) => set_error.set(Some(err)),
+        }
+    };
+
+    let id = format!("{}-formula", group);
+    let label_for = id.clone();
+
+    view! {
+        <div class="input-vertical input-formula">
+            <label for=label_for>
+                {move || input_text.get().get(label).cloned().unwrap_or(label.to_string())}
+            </label>
+            <input
+                id=id
+                type="text"
+                spellcheck="false"
+                placeholder=placeholder
+                title=move || {
+                    input_text
+                        .get()
+                        .get("Leave empty for the built-in density function")
+                        .cloned()
+                        .unwrap_or(String::from("Leave empty for the built-in density function"))
+                }
+                prop:value=move || formula.get()
+                on:change=on_change
+            />
+            <Show when=move || error.get().is_some()>
+                <span class="input-formula-error">
+                    {move || {
+                        input_text
+                            .get()
+                            .get("Invalid formula")
+                            .cloned()
+                            .unwrap_or(String::from("Invalid formula"))
+                    }}
+                    ": "
+                    {move || error.get().unwrap_or_default()}
+                </span>
+            </Show>
+        </div>
+    }
+}
+
+// Class of the highlight of a switch, on its left option if the condition holds
+fn highlight(left: bool) -> &'static str {
+    if left {
+        "input-switch-highlight input-switch-highlight-left"
+    } else {
+        "input-switch-highlight input-switch-highlight-right"
+    }
+}
+
+#[component]
+fn VirialInputs(input_text: Memo<HashMap<String, String>>) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
+    let translate = move |key: &'static str| {
+        move || input_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+    let virial = Memo::new(move |_| model.with(|model| model.virial().copied()));
+    // Set if the current halo has no r₂₀₀, so it can not be described by its virial values
+    let (error, set_error) = signal(None::<String>);
+
+    let on_redshift = move |ev: Event| {
+        let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+        match input.value().parse::<f64>() {
+            Ok(value) if value.is_finite() => model.update(|model| {
+                model.update_virial(|virial| virial.redshift = value.clamp(REDSHIFT_LIMITS.0, REDSHIFT_LIMITS.1));
+            }),
+            _ => input.set_value(&virial.get_untracked().map(|virial| virial.redshift).unwrap_or_default().to_string()),
+        }
+    };
+
+    view! {
+        <div class="section">
+            <div class="input-horizontal">
+                <button
+                    class="input-switch"
+                    on:click=move |_| model.update(|model| {
+                        let enabled = model.virial().is_none();
+                        set_error.set(model.set_virial(enabled).err());
+                    })
+                >
+                    <span class="input-switch-label">{translate("Initial density and scale length")}</span>
+                    <span class="input-switch-label">{translate("Virial mass and concentration")}</span>
+                    <span class=move || highlight(virial.get().is_none())></span>
+                </button>
+            </div>
+            <Show when=move || error.get().is_some()>
+                <span class="input-formula-error">
+                    {move || {
+                        let error = error.get().unwrap_or_default();
+                        input_text.get().get(&error).cloned().unwrap_or(error)
+                    }}
+                </span>
+            </Show>
+        </div>
+        <Show when=move || virial.get().is_some()>
+            <div class="section">
+                <div class="input-horizontal">
+                    <button
+                        class="input-switch"
+                        on:click=move |_| model.update(|model| {
+                            model.update_virial(|virial| virial.concentration_relation = !virial.concentration_relation);
+                        })
+                    >
+                        <span class="input-switch-label">{translate("Concentration by hand")}</span>
+                        <span class="input-switch-label">{translate("Concentration from the c-M relation")}</span>
+                        <span class=move || {
+                            highlight(!virial.get().is_some_and(|virial| virial.concentration_relation))
+                        }></span>
+                    </button>
+                </div>
+            </div>
+            <div class="input-section">
+                <div class="input-vertical input-number">
+                    <label for="redshift">{translate("Redshift")}</label>
+                    <input
+                        id="redshift"
+                        type="number"
+                        min=REDSHIFT_LIMITS.0.to_string()
+                        max=REDSHIFT_LIMITS.1.to_string()
+                        step="any"
+                        prop:value=move || virial.get().map(|virial| virial.redshift).unwrap_or_default().to_string()
+                        on:change=on_redshift
+                    />
+                </div>
+                <span class="input-virial-values">
+                    {move || {
+                        virial
+                            .get()
+                            .map(|virial| {
+                                format!(
+                                    "r₂₀₀ = {:.1} kpc, V₂₀₀ = {:.1} km/s, c = {:.2}",
+                                    virial.radius(),
+                                    virial.velocity(),
+                                    virial.concentration(),
+                                )
+                            })
+                            .unwrap_or_default()
+                    }}
+                </span>
+            </div>
+        </Show>
+    }
+}
+
+#[component]
+fn TruncationInputs(input_text: Memo<HashMap<String, String>>) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
+    let translate = move |key: &'static str| {
+        move || input_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    view! {
+        <div class="section">
+            <div class="input-horizontal input-truncation">
+                <span>{translate("Halo truncation")}</span>
+                <OptionToggle
+                    selected=Signal::derive(move || model.with(|model| model.halo.truncation))
+                    on_select=move |target| model.update(|model| model.halo.truncation = target)
+                    options=vec![
+                        (HaloTruncation::None, "Unlimited"),
+                        (HaloTruncation::Virial, "At r₂₀₀"),
+                        (HaloTruncation::Exponential, "Exponential cutoff"),
+                    ]
+                    text=input_text
+                />
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn ContractionInputs(input_text: Memo<HashMap<String, String>>) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
+    let translate = move |key: &'static str| {
+        move || input_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    view! {
+        <div class="section">
+            <div class="input-horizontal input-contraction">
+                <span>{translate("Adiabatic contraction")}</span>
+                <OptionToggle
+                    selected=Signal::derive(move || model.with(|model| model.halo.contraction))
+                    on_select=move |target| model.update(|model| model.halo.contraction = target)
+                    options=vec![
+                        (HaloContraction::None, "None"),
+                        (HaloContraction::Blumenthal, "Blumenthal"),
+                        (HaloContraction::Gnedin, "Gnedin"),
+                    ]
+                    text=input_text
+                />
+            </div>
+            // The contraction assumes spherical shells, flattened halos are left as they are
+            <Show when=move || {
+                model.with(|model| {
+                    model.halo.contraction != HaloContraction::None
+                        && model.halo.axis_ratio != 1.0
+                        && model.halo.custom.is_none()
+                })
+            }>
+                <span class="input-note">{translate("Only spherical halos (axis ratio 1) are contracted")}</span>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn GridInputs(input_text: Memo<HashMap<String, String>>) -> impl IntoView {
+    let grid = expect_context::<RwSignal<RadialGrid>>();
+
+    let translate = move |key: &'static str| {
+        move || input_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    // Invalid values are ignored, the input shows the current value again
+    let on_max_radius = move |ev: Event| {
+        let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+        match input.value().parse::<f64>() {
+            Ok(value) if value.is_finite() => grid.update(|grid| {
+                grid.max_radius = value.clamp(MAX_RADIUS_LIMITS.0, MAX_RADIUS_LIMITS.1);
+            }),
+            _ => input.set_value(&grid.get_untracked().max_radius.to_string()),
+        }
+    };
+    let on_samples = move |ev: Event| {
+        let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+        match input.value().parse::<usize>() {
+            Ok(value) => grid.update(|grid| {
+                grid.samples = value.clamp(SAMPLES_LIMITS.0, SAMPLES_LIMITS.1);
+            }),
+            _ => input.set_value(&grid.get_untracked().samples.to_string()),
+        }
+    };
+
+    view! {
+        <div class="input-section">
+            <div class="input-vertical input-number">
+                <label for="max-radius">{translate("Maximum radius")} " (kpc)"</label>
+                <input
+                    id="max-radius"
+                    type="number"
+                    min=MAX_RADIUS_LIMITS.0.to_string()
+                    max=MAX_RADIUS_LIMITS.1.to_string()
+                    step="any"
+                    prop:value=move || grid.with(|grid| grid.max_radius.to_string())
+                    on:change=on_max_radius
+                />
+            </div>
+            <div class="input-vertical input-number">
+                <label for="samples">
+                    {move || {
+                        if grid.with(|grid| grid.adaptive) {
+                            translate("Maximum number of samples")()
+                        } else {
+                            translate("Number of samples")()
+                        }
+                    }}
+                </label>
+                <input
+                    id="samples"
+                    type="number"
+                    min=SAMPLES_LIMITS.0.to_string()
+                    max=SAMPLES_LIMITS.1.to_string()
+                    step="1"
+                    prop:value=move || grid.with(|grid| grid.samples.to_string())
+                    on:change=on_samples
+                />
+            </div>
+        </div>
+        <div class="section">
+            <div class="input-horizontal">
+                <button
+                    class="input-switch"
+                    on:click=move |_| {
+                        grid.update(|grid| {
+                            grid.spacing = match grid.spacing {
+                                Spacing::Linear => Spacing::Logarithmic,
+                                Spacing::Logarithmic => Spacing::Linear,
+                            };
+                        });
+                    }
+                >
+                    <span class="input-switch-label">{translate("Linear spacing")}</span>
+                    <span class="input-switch-label">{translate("Logarithmic spacing")}</span>
+                    <span class=move || highlight(grid.with(|grid| grid.spacing == Spacing::Linear))></span>
+                </button>
+            </div>
+        </div>
+        <div class="section">
+            <div class="input-horizontal">
+                <button
+                    class="input-switch"
+                    on:click=move |_| grid.update(|grid| grid.adaptive = !grid.adaptive)
+                >
+                    <span class="input-switch-label">{translate("Uniform sampling")}</span>
+                    <span class="input-switch-label">{translate("Adaptive sampling")}</span>
+                    <span class=move || highlight(!grid.with(|grid| grid.adaptive))></span>
+                </button>
+            </div>
+        </div>
     }
 }
 
@@ -98,79 +499,39 @@ fn wheel_handle(
 pub fn Inputs(
     set_mode: WriteSignal<String>,
     mode: ReadSignal<String>,
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
-    set_slider_values: WriteSignal<(f64, f64, f64, f64)>,
-    iso_nfw: ReadSignal<bool>,
-    set_iso_nfw: WriteSignal<bool>,
     text: ReadSignal<Translation>,
 ) -> impl IntoView {
     // Get input section from text
-    let input_text: Memo<std::collections::HashMap<String, String>> =
+    let input_text: Memo<HashMap<String, String>> =
         Memo::new(move |_| text.get().0.get("inputs").cloned().unwrap_or_default());
 
-    // Config for each slider
-    let slider_configs = vec![
-        // Disk density
-        SliderConfig {
-            min_value: 1.0,
-            max_value: 200.0,
-            step: 1.0,
-            factor: 1.0 / 100.0,
-        },
-        // Disk scalelength
-        SliderConfig {
-            min_value: 20.0,
-            max_value: 880.0,
-            step: 1.0,
-            factor: 1.0 / 100.0,
-        },
-        // Halo density
-        SliderConfig {
-            min_value: 1.0,
-            max_value: 303.0,
-            step: 1.0,
-            factor: 10.0e-24,
-        },
-        // Halo scalelength
-        SliderConfig {
-            min_value: 1.0,
-            max_value: 3180.0,
-            step: 1.0,
-            factor: 1.0 / 100.0,
-        },
-    ];
+    let model = expect_context::<RwSignal<GalaxyModel>>();
 
-    // Create handlers for scroll and normal input
-    let wheel_handlers: Vec<_> = (0..4)
-        .map(|i| {
-            let cfg = slider_configs[i].clone();
-            wheel_handle(i, cfg, slider_values, set_slider_values)
-        })
-        .collect();
-
-    let input_handlers: Vec<_> = (0..4)
-        .map(|i| {
-            let cfg = slider_configs[i].clone();
-            let slider_values = slider_values.clone();
-            let set_slider_values = set_slider_values.clone();
-            move |ev: Event| {
-                let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
-                let raw_value = input.value().parse::<f64>().unwrap_or(cfg.min_value);
-                let old_values = slider_values.get();
-                let new_value = raw_value * cfg.factor;
-                let new_values = match i {
-                    0 => (new_value, old_values.1, old_values.2, old_values.3),
-                    1 => (old_values.0, new_value, old_values.2, old_values.3),
-                    2 => (old_values.0, old_values.1, new_value, old_values.3),
-                    3 => (old_values.0, old_values.1, old_values.2, new_value),
-                    _ => old_values,
-                };
-                set_slider_values(new_values);
+    // Parameters grouped by component, sliders are only rebuilt if they change
+    let parameter_groups = Memo::new(move |_| {
+        let mut groups: Vec<Vec<ModelParameter>> = Vec::new();
+        for parameter in model.with(|model| model.parameters()) {
+            match groups.last_mut() {
+                Some(group) if group[0].group == parameter.group => group.push(parameter),
+                _ => groups.push(vec![parameter]),
             }
-        })
-        .collect();
+        }
+        groups
+    });
 
-    let mode_options = ["velocity", "mass", "density"];
+    // Modes and their labels, in the order of the range
+    let mode_options = [
+        ("velocity", "Velocity"),
+        ("mass", "Mass"),
+        ("density", "Density"),
+        ("potential", "Potential"),
+        ("rotation", "Rotation"),
+        ("animation", "Animation"),
+        ("orbit", "Orbits"),
+        ("inversion", "Missing mass"),
+        ("rar", "Acceleration relation"),
+        ("tully_fisher", "Tully-Fisher"),
+    ];
 
     view! {
         <div id="inputs">
@@ -180,11 +541,11 @@ pub fn Inputs(
                         id="mode-range"
                         type="range"
                         min="0"
-                        max="2"
+                        max=(mode_options.len() - 1).to_string()
                         value=move || {
                             mode_options
                                 .iter()
-                                .position(|&m| m == mode.get())
+                                .position(|&(m, _)| m == mode.get())
                                 .unwrap_or(0)
                                 .to_string()
                         }
@@ -197,166 +558,59 @@ pub fn Inputs(
                                     .unwrap()
                                     .value()
                                     .parse::<usize>()
-                                    .unwrap()])
+                                    .unwrap()]
+                                    .0)
                                     .to_string(),
                             );
                         }
                     />
                 </div>
                 <div id="mode-labels">
-                    <span>
-                        {move || {
-                            input_text
-                                .get()
-                                .get("Velocity")
-                                .cloned()
-                                .unwrap_or(String::from("Velocity"))
-                        }}
-                    </span>
-                    <span>
-                        {move || {
-                            input_text.get().get("Mass").cloned().unwrap_or(String::from("Mass"))
-                        }}
-                    </span>
-                    <span>
-                        {move || {
-                            input_text
-                                .get()
-                                .get("Density")
-                                .cloned()
-                                .unwrap_or(String::from("Density"))
-                        }}
-                    </span>
-                </div>
-            </div>
-            <div class="input-section">
-                <div class="input-vertical">
-                    <div id="density-disk" class="input-range-cont-with-value">
-                        <label for="density-disk">
-                            {move || {
-                                input_text
-                                    .get()
-                                    .get("Initial surface density of the disk")
-                                    .cloned()
-                                    .unwrap_or(String::from("Initial surface density of the disk"))
-                            }}
-                        </label>
-                        <div class="input-small-range-inner-cont">
-                            <input
-                                class="small-range"
-                                id="density-disk"
-                                type="range"
-                                min="1"
-                                max="200"
-                                value={
-                                    let (v0, _, _, _) = slider_values.get_untracked();
-                                    (v0 / slider_configs[0].factor).to_string()
-                                }
-                                on:input=input_handlers[0].clone()
-                                on:wheel=wheel_handlers[0].clone()
-                            />
-                        </div>
-                        <span>{move || format!("{:.2}", slider_values.get().0)} " kg/m²"</span>
-                    </div>
-                </div>
-                <div class="input-vertical">
-                    <div id="scale-disk" class="input-range-cont-with-value">
-                        <label for="scale-disk">
-                            {move || {
-                                input_text
-                                    .get()
-                                    .get("Scale length of the disk")
-                                    .cloned()
-                                    .unwrap_or(String::from("Scale length of the disk"))
-                            }}
-                        </label>
-                        <div class="input-small-range-inner-cont">
-                            <input
-                                class="small-range"
-                                id="density-disk"
-                                type="range"
-                                min="20"
-                                max="880"
-                                value={
-                                    let (_, v1, _, _) = slider_values.get_untracked();
-                                    (v1 / slider_configs[1].factor).to_string()
-                                }
-                                on:input=input_handlers[1].clone()
-                                on:wheel=wheel_handlers[1].clone()
-                            />
-                        </div>
-                        <span>{move || format!("{:.2}", slider_values.get().1)} " kpc"</span>
-                    </div>
-                </div>
-            </div>
-            <div class="input-section">
-                <div class="input-vertical">
-                    <div id="density-halo" class="input-range-cont-with-value">
-                        <label for="density-halo">
-                            {move || {
-                                input_text
-                                    .get()
-                                    .get("Initial density of the halo")
-                                    .cloned()
-                                    .unwrap_or(String::from("Initial density of the halo"))
-                            }}
-                        </label>
-                        <div class="input-small-range-inner-cont">
-                            <input
-                                class="small-range"
-                                id="density-halo"
-                                type="range"
-                                min="1"
-                                max="303"
-                                value={
-                                    let (_, _, v2, _) = slider_values.get_untracked();
-                                    (v2 / slider_configs[2].factor).to_string()
-                                }
-                                on:input=input_handlers[2].clone()
-                                on:wheel=wheel_handlers[2].clone()
-                            />
-
-                        </div>
-                        <span>{move || format!("{:.2e}", slider_values.get().2)} " kpc"</span>
-                    </div>
-                </div>
-                <div class="input-vertical">
-                    <div id="scale-haloe" class="input-range-cont-with-value">
-                        <label for="scale-halo">
-                            {move || {
-                                input_text
-                                    .get()
-                                    .get("Scale length of the halo")
-                                    .cloned()
-                                    .unwrap_or(String::from("Scale length of the halo"))
-                            }}
-                        </label>
-                        <div class="input-small-range-inner-cont">
-                            <input
-                                class="small-range"
-                                id="density-halo"
-                                type="range"
-                                min="1"
-                                max="3180"
-                                value={
-                                    let (_, _, _, v3) = slider_values.get_untracked();
-                                    (v3 / slider_configs[3].factor).to_string()
-                                }
-                                on:input=input_handlers[3].clone()
-                                on:wheel=wheel_handlers[3].clone()
-                            />
-
-                        </div>
-                        <span>{move || format!("{:.2}", slider_values.get().3)} " kg/m³"</span>
-                    </div>
+                    {mode_options
+                        .iter()
+                        .map(|&(_, label)| {
+                            view! {
+                                <span>
+                                    {move || {
+                                        input_text.get().get(label).cloned().unwrap_or(String::from(label))
+                                    }}
+                                </span>
+                            }
+                        })
+                        .collect_view()}
                 </div>
             </div>
+            {move || {
+                parameter_groups
+                    .get()
+                    .into_iter()
+                    .map(|group| {
+                        view! {
+                            <div class="input-section">
+                                {group
+                                    .into_iter()
+                                    .map(|parameter| {
+                                        view! { <ParameterSlider parameter=parameter input_text=input_text /> }
+                                    })
+                                    .collect_view()}
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
             <div class="section">
                 <div class="input-horizontal">
                     <button
                         class="input-switch"
                         on:click=move |_| {
-                            set_iso_nfw(!iso_nfw.get());
+                            model.update(|model| {
+                                model.halo.profile = match model.halo.profile {
+                                    HaloProfile::Isothermal => HaloProfile::Nfw,
+                                    HaloProfile::Nfw => HaloProfile::Isothermal,
+                                };
+                                // The virial values only describe NFW halos, switching them off always succeeds
+                                let _ = model.set_virial(false);
+                            });
                         }
                     >
                         <span class="input-switch-label">
@@ -386,15 +640,31 @@ pub fn Inputs(
                             }}
                         </span>
                         <span class=move || {
-                            if iso_nfw.get() {
-                                "input-switch-highlight input-switch-highlight-left".to_string()
-                            } else {
-                                "input-switch-highlight input-switch-highlight-right".to_string()
-                            }
+                            highlight(model.with(|model| model.halo.profile == HaloProfile::Isothermal))
                         }></span>
                     </button>
                 </div>
             </div>
+            <Show when=move || model.with(|model| model.halo.profile == HaloProfile::Nfw)>
+                <VirialInputs input_text=input_text />
+            </Show>
+            <TruncationInputs input_text=input_text />
+            <ContractionInputs input_text=input_text />
+            <GridInputs input_text=input_text />
+            <div class="input-section">
+                <FormulaInput
+                    group="disk"
+                    label="Custom density of the disk"
+                    placeholder="rho0 * exp(-r/a)"
+                    input_text=input_text
+                />
+                <FormulaInput
+                    group="halo"
+                    label="Custom density of the halo"
+                    placeholder="rho0 / (1 + (r/a)^2)"
+                    input_text=input_text
+                />
+            </div>
         </div>
     }
 }
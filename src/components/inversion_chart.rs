@@ -0,0 +1,158 @@
+use leptos::prelude::*;
+use leptos_chartistry::*;
+use std::collections::HashMap;
+use crate::{
+    elements::{default_chart::{apply_range, DefaultChart}, toggle_button::OptionToggle}, utils::{
+        axis_settings::AxisSettings,
+        galaxy_model::GalaxyModel,
+        inversion::{infer_dark_matter, interpolate, InferredPoint},
+        observed::ngc_3198,
+        radial_grid::RadialGrid,
+        translation::{create_text_signal, Translation}
+    }
+};
+
+// Quantity shown by the missing mass chart
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InversionView {
+    #[default]
+    Mass,
+    Density,
+}
+
+impl InversionView {
+    // Names of the curves, the inferred ones first
+    fn curve_names(self) -> Vec<&'static str> {
+        match self {
+            Self::Mass => vec!["Dynamical mass", "Implied dark matter", "Disk", "Halo"],
+            Self::Density => vec!["Implied dark matter", "Halo"],
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+struct InversionPoint {
+    x: f64,
+    ys: Vec<f64>
+}
+
+// Model curves at the sampled radii and at the observed ones, with the inferred values interpolated
+fn get_inversion_points(model: &GalaxyModel, grid: &RadialGrid, view: InversionView) -> Vec<(f64, Vec<f64>)> {
+    let [disk, halo] = model.disk_and_halo();
+    let inferred = infer_dark_matter(&ngc_3198(), disk.as_ref());
+
+    let inferred_values: Vec<fn(&InferredPoint) -> f64> = match view {
+        InversionView::Mass => vec![|point| point.dynamical_mass, |point| point.dark_mass],
+        InversionView::Density => vec![|point| point.dark_density],
+    };
+    let model_values = |x: f64| match view {
+        InversionView::Mass => vec![disk.mass(x), halo.mass(x)],
+        InversionView::Density => vec![halo.density(x)],
+    };
+
+    let mut points = grid.sample(model_values);
+    points.extend(
+        inferred
+            .iter()
+            .filter(|point| point.radius <= grid.max_radius)
+            .map(|point| (point.radius, model_values(point.radius))),
+    );
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    points.dedup_by(|a, b| a.0 == b.0);
+
+    points
+        .into_iter()
+        .map(|(x, model_ys)| {
+            let mut ys: Vec<f64> = inferred_values.iter().map(|&value| interpolate(&inferred, x, value)).collect();
+            ys.extend(model_ys);
+            (x, ys)
+        })
+        .collect()
+}
+
+#[component]
+pub fn InversionChart(
+    text: ReadSignal<Translation>,
+    view: RwSignal<InversionView>,
+    // Axes of the mass and of the density view
+    mass_axes: RwSignal<AxisSettings>,
+    density_axes: RwSignal<AxisSettings>,
+) -> impl IntoView {
+    // Get inversion section of text
+    let inversion_text: Memo<HashMap<String, String>> =
+        Memo::new(move |_| text.get().0.get("inversion").cloned().unwrap_or_default());
+
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let grid = expect_context::<RwSignal<RadialGrid>>();
+
+    let axes = move || match view.get() {
+        InversionView::Mass => mass_axes,
+        InversionView::Density => density_axes,
+    };
+
+    let inversion_points = Memo::new(move |_| {
+        let view = view.get();
+        let axes = axes().get();
+
+        // Positions on the (possibly logarithmic) axes, clipped to the limits
+        axes.clipped_positions(model.with(|model| grid.with(|grid| get_inversion_points(model, grid, view))))
+            .into_iter()
+            .map(|(x, ys)| InversionPoint { x, ys })
+            .collect::<Vec<_>>()
+    });
+
+    let max_radius = Memo::new(move |_| grid.with(|grid| grid.max_radius));
+
+    move || {
+        let current_view = view.get();
+        let names = current_view.curve_names();
+        // The inferred curves are drawn thicker
+        let inferred = names.len() / 2;
+        let mut series = Series::new(|data: &InversionPoint| data.x);
+
+        for (i, name) in names.into_iter().enumerate() {
+            series = series.line(Line::new(move |data: &InversionPoint| data.ys.get(i).copied().unwrap_or(f64::NAN))
+                .with_name_dyn(
+                    create_text_signal(inversion_text, name.to_string())
+                )
+                .with_width(if i < inferred { 3.0 } else { 1.5 })
+            );
+        }
+
+        let axes = axes();
+        let series = apply_range(series, &axes.get(), max_radius.get());
+
+        let y_label = match current_view {
+            InversionView::Mass => "Mass (10^10 * M☉)",
+            InversionView::Density => "Density (10^10 * M☉)",
+        };
+
+        view! {
+            <DefaultChart
+                y_label=y_label.to_string()
+                x_label="Radius (kpc)".to_string()
+                series=series
+                data=inversion_points
+                primary=true
+                label_text=inversion_text
+                axes=axes
+            >
+                <OptionToggle
+                    selected=view
+                    on_select=move |target| view.set(target)
+                    options=vec![
+                        (InversionView::Mass, "Enclosed mass"),
+                        (InversionView::Density, "Density"),
+                    ]
+                    text=inversion_text
+                />
+                <span>
+                    {move || {
+                        let key = "From the rotation curve of NGC 3198 minus the modelled disk";
+                        inversion_text.get().get(key).cloned().unwrap_or(key.to_string())
+                    }}
+                </span>
+            </DefaultChart>
+        }
+    }
+}
@@ -1,175 +1,257 @@
 use leptos::prelude::*;
 use leptos_chartistry::*;
+use std::collections::HashMap;
 use crate::{
-    elements::default_chart::DefaultChart, utils::{
-        calculate_mass::*,
-        intersection::x_intersection, translation::{create_text_signal, Translation}
+    elements::{default_chart::{apply_range, DefaultChart}, toggle_button::OptionToggle}, utils::{
+        axis_settings::AxisSettings,
+        galaxy_model::{GalaxyModel, COSMIC_BARYON_FRACTION},
+        curve_features::dominance_radius,
+        mass_component::MassComponent,
+        radial_grid::RadialGrid,
+        translation::{create_text_signal, Translation}
     }
 };
 
-// Vertical limit of chart
-const CHART_BOUND: f64 = 30.0;
-
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 struct MassPoint {
     x: f64,
-    y1: f64,
-    y2: f64
+    // Mass of each component
+    ys: Vec<f64>
 }
 
 impl MassPoint {
-    fn new(x: f64, y1: f64, y2: f64) -> Self {
+    fn new(x: f64, ys: Vec<f64>) -> Self {
         Self {
             x,
-            y1,
-            y2
+            ys
         }
     }
 }
 
-// Convert units
-fn halo_factor() -> f64 {
-    (3.09 * 10.0_f64.powi(18)) / 2.0
+fn get_mass_points(components: &[Box<dyn MassComponent>], grid: &RadialGrid) -> Vec<MassPoint> {
+    // Compute points
+    grid.sample(|x| components.iter().map(|component| component.mass(x)).collect())
+        .into_iter()
+        .map(|(x, ys)| MassPoint::new(x, ys))
+        .collect()
 }
 
-fn disk_factor() -> f64 {
-    10.0_f64.powi(-1)
+// Quantity shown by the mass chart
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MassView {
+    #[default]
+    Enclosed,
+    // Share of dark matter in the enclosed mass
+    DarkMatterFraction,
 }
 
-fn get_mass_points(
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
-    iso_nfw: ReadSignal<bool>
-) -> Vec<MassPoint> {
-    // Retrieve Properties from signal
-    let properties = slider_values.get();
-    let iso_nfw_resolved = iso_nfw.get();
+// Buttons of the views, shown below both charts
+const VIEW_OPTIONS: [(MassView, &str); 2] = [
+    (MassView::Enclosed, "Enclosed mass"),
+    (MassView::DarkMatterFraction, "Dark matter fraction"),
+];
 
-    // Compute points
-    let mut mass_points: Vec<MassPoint> = Vec::new();
+#[derive(PartialEq, Clone, Debug)]
+struct FractionPoint {
+    x: f64,
+    dark: f64,
+    baryonic: f64,
+    // Vertical line at the radius from which dark matter dominates, NaN elsewhere
+    dominance: f64,
+}
 
-    for i in (0..182).map(|x| x as f64 * 0.25) {
-        let x: f64 = i as f64;
-        let y1: f64 = mass_disk(x, properties.0, properties.1) * disk_factor();
-        let y2: f64 = mass_halo(x, properties.2, properties.3, iso_nfw_resolved) * halo_factor();
+fn get_fraction_points(model: &GalaxyModel, grid: &RadialGrid, dominance: Option<f64>) -> Vec<FractionPoint> {
+    let dark_matter_fraction = model.dark_matter_fraction();
+    let point = |x: f64, dark: f64, dominance: f64| FractionPoint {
+        x,
+        dark,
+        baryonic: 1.0 - dark,
+        dominance,
+    };
+    let mut points: Vec<FractionPoint> = grid
+        .sample(|x| vec![dark_matter_fraction(x)])
+        .into_iter()
+        .map(|(x, ys)| point(x, ys[0], f64::NAN))
+        .collect();
 
-        mass_points.push(MassPoint::new(x, y1, y2));
+    // Two copies of the point at the radius draw the marker from the bottom to the top
+    if let Some(radius) = dominance {
+        let dark = dark_matter_fraction(radius);
+        let i = points.partition_point(|point| point.x < radius);
+        points.splice(i..i, [point(radius, dark, 0.0), point(radius, dark, 1.0)]);
     }
-
-    mass_points
+    points
 }
 
-// Check for an intersection at CHART_BOUND; If exists: compute point of intersection.
-fn check_intersection(
-    i: usize,
-    mass_points_no_bound: &Vec<MassPoint>,
-    mass_points: &mut Vec<MassPoint>,
-    disk_halo: bool,
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
-    iso_nfw: ReadSignal<bool>
-) {
-    // Check if first point
-    if i == 0 {
-        return;
+#[component]
+pub fn MassChart(
+    text: ReadSignal<Translation>,
+    axes: RwSignal<AxisSettings>,
+    view: RwSignal<MassView>,
+) -> impl IntoView {
+    // Get mass section of text
+    let mass_text: Memo<HashMap<String, String>> =
+        Memo::new(move |_| text.get().0.get("mass").cloned().unwrap_or_default());
+
+    move || match view.get() {
+        MassView::Enclosed => view! { <EnclosedMassChart mass_text axes view /> }.into_any(),
+        MassView::DarkMatterFraction => view! { <DarkMatterFractionChart mass_text view /> }.into_any(),
     }
+}
 
-    // Retrieve values
-    let current = mass_points_no_bound[i];
-    let prev = mass_points_no_bound[i - 1];
-    let (x2, y2) = (current.x, if disk_halo { current.y1 } else { current.y2 });
-    let (x1, y1) = (prev.x, if disk_halo { prev.y1 } else { prev.y2 });
+#[component]
+fn EnclosedMassChart(
+    mass_text: Memo<HashMap<String, String>>,
+    axes: RwSignal<AxisSettings>,
+    view: RwSignal<MassView>,
+) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let grid = expect_context::<RwSignal<RadialGrid>>();
 
-    // No intersection if y1 <= CHART_BOUND or y2 >= CHART_BOUND
-    if y2 <= CHART_BOUND || y1 >= CHART_BOUND {
-        return;
-    }
+    let mass_points = Memo::new(move |_| {
+        let components = model.get().components();
+        let axes = axes.get();
 
-    // Compute intersection and add Point
-    let intersect_x = x_intersection(x1, y1, x2, y2, CHART_BOUND);
-    let intersection_point: MassPoint = if disk_halo {
-        let halo_val = mass_halo(intersect_x, slider_values.get().2, slider_values.get().3, iso_nfw.get()) * halo_factor();
-        MassPoint::new(
-            intersect_x,
-            CHART_BOUND,
-            if halo_val < CHART_BOUND {
-                halo_val
-            } else {
-                f64::NAN
-            }
-        )
-    } else {
-        let disk_val = mass_disk(intersect_x, slider_values.get().0, slider_values.get().1) * disk_factor();
-        MassPoint::new(
-            intersect_x,
-            if disk_val < CHART_BOUND {
-                disk_val
-            } else {
-                f64::NAN
-            },
-            CHART_BOUND
-        )
-    };
+        // Positions on the (possibly logarithmic) axes, clipped to the limits
+        axes.clipped_positions(grid.with(|grid| get_mass_points(&components, grid)).into_iter().map(|mass| (mass.x, mass.ys)))
+            .into_iter()
+            .map(|(x, ys)| MassPoint::new(x, ys))
+            .collect::<Vec<_>>()
+    });
+
+    // Names of the components, the chart is only rebuilt if they change
+    let component_names = Memo::new(move |_| {
+        model.with(|model| {
+            model
+                .components()
+                .iter()
+                .map(|component| component.name())
+                .collect::<Vec<_>>()
+        })
+    });
 
-    mass_points.push(intersection_point);
+    let max_radius = Memo::new(move |_| grid.with(|grid| grid.max_radius));
+
+    move || {
+        // One line for each component
+        let mut series = Series::new(|data: &MassPoint| data.x);
+
+        for (i, name) in component_names.get().into_iter().enumerate() {
+            series = series.line(Line::new(move |data: &MassPoint| data.ys.get(i).copied().unwrap_or(f64::NAN))
+                .with_name_dyn(
+                    create_text_signal(mass_text, name.to_string())
+                )
+                .with_width(3.0)
+            );
+        }
+
+        let series = apply_range(series, &axes.get(), max_radius.get());
+
+        view! {
+            <DefaultChart
+                y_label="Mass (10^10 * M☉)".to_string()
+                x_label="Radius (kpc)".to_string()
+                series={series}
+                data=mass_points
+                primary=true
+                label_text=mass_text
+                axes=axes
+            >
+                <OptionToggle
+                    selected=view
+                    on_select=move |target| view.set(target)
+                    options=VIEW_OPTIONS.to_vec()
+                    text=mass_text
+                />
+            </DefaultChart>
+        }
+    }
 }
 
 #[component]
-pub fn MassChart(
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
-    iso_nfw: ReadSignal<bool>,
-    text: ReadSignal<Translation>
+fn DarkMatterFractionChart(
+    mass_text: Memo<HashMap<String, String>>,
+    view: RwSignal<MassView>,
 ) -> impl IntoView {
-    // Get mass section of text
-    let mass_text: Memo<std::collections::HashMap<String, String>> =
-        Memo::new(move |_| text.get().0.get("mass").cloned().unwrap_or_default());
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let grid = expect_context::<RwSignal<RadialGrid>>();
 
-    let mass_points = Memo::new(move |_| {
-        let mass_points_no_bound = get_mass_points(slider_values, iso_nfw);
-        
-        let mut mass_points = Vec::new();
-
-        for (i, mass) in mass_points_no_bound.iter().enumerate() {
-            let mut mass = *mass;
-            // Check if fits into CHART_BOUND, otherwise use NaN
-            if mass.y1 > CHART_BOUND {
-                check_intersection(i, &mass_points_no_bound, &mut mass_points, true, slider_values, iso_nfw);
-                mass.y1 = f64::NAN;
-            }
-
-            if mass.y2 > CHART_BOUND {
-                check_intersection(i, &mass_points_no_bound, &mut mass_points, false, slider_values, iso_nfw);
-                mass.y2 = f64::NAN;
-            }
-
-            mass_points.push(mass);
-        }
+    let max_radius = Memo::new(move |_| grid.with(|grid| grid.max_radius));
 
-        mass_points
+    // First radius from which dark matter makes up more than half of the enclosed mass
+    let dominance = Memo::new(move |_| model.with(|model| dominance_radius(model, max_radius.get())));
+
+    let fraction_points = Memo::new(move |_| {
+        model.with(|model| grid.with(|grid| get_fraction_points(model, grid, dominance.get())))
     });
 
-    let series = Series::new(|data: &MassPoint| data.x)
-        .line(Line::new(|data: &MassPoint| data.y1)
-            .with_name_dyn(
-                create_text_signal(mass_text, "Disk".to_string())
-            )
-            .with_width(3.0)
+    let translate = move |key: &'static str| mass_text.get().get(key).cloned().unwrap_or(key.to_string());
+
+    // Where dark matter starts to dominate and how the outer value compares to the cosmic one
+    let summary = move || {
+        let points = fraction_points.get();
+        let dominance = match dominance.get() {
+            Some(radius) => format!("{}: {:.2} kpc", translate("Dark matter dominates from"), radius),
+            None => translate("Dark matter does not dominate within the chart"),
+        };
+        let outer = points.last().map_or(f64::NAN, |point| point.baryonic);
+        format!(
+            "{}. {}: {:.1}% ({}: {:.1}%)",
+            dominance,
+            translate("Baryon fraction at the maximum radius"),
+            outer * 100.0,
+            translate("cosmic"),
+            COSMIC_BARYON_FRACTION * 100.0,
         )
-        .line(Line::new(|data: &MassPoint| data.y2)
-            .with_name_dyn(
-                create_text_signal(mass_text, "Halo".to_string())
+    };
+
+    move || {
+        let series = Series::new(|data: &FractionPoint| data.x)
+            .line(
+                Line::new(|data: &FractionPoint| data.dark)
+                    .with_name_dyn(create_text_signal(mass_text, "Dark matter fraction".to_string()))
+                    .with_width(3.0),
             )
-            .with_width(3.0)
-        )
-        .with_y_range(0.0, CHART_BOUND)
-        .with_x_range(0.0, 45.0);
-
-    view! {
-        <DefaultChart
-            y_label="Mass (10^10 * M☉)".to_string()
-            x_label="Radius (kpc)".to_string()
-            series={series}
-            data=mass_points
-            primary=true
-            label_text=mass_text
-        />
+            .line(
+                Line::new(|data: &FractionPoint| data.baryonic)
+                    .with_name_dyn(create_text_signal(mass_text, "Baryon fraction".to_string()))
+                    .with_width(3.0),
+            )
+            .line(
+                Line::new(|_: &FractionPoint| COSMIC_BARYON_FRACTION)
+                    .with_name_dyn(create_text_signal(mass_text, "Cosmic baryon fraction".to_string()))
+                    .with_width(1.5),
+            )
+            .line(
+                Line::new(|_: &FractionPoint| 0.5)
+                    .with_name_dyn(create_text_signal(mass_text, "Half of the enclosed mass".to_string()))
+                    .with_width(1.0),
+            )
+            .line(
+                Line::new(|data: &FractionPoint| data.dominance)
+                    .with_name_dyn(create_text_signal(mass_text, "Dark matter dominates".to_string()))
+                    .with_width(1.5),
+            )
+            .with_y_range(0.0, 1.0)
+            .with_x_range(0.0, max_radius.get());
+
+        view! {
+            <DefaultChart
+                y_label="Fraction of the enclosed mass".to_string()
+                x_label="Radius (kpc)".to_string()
+                series=series
+                data=fraction_points
+                primary=true
+                label_text=mass_text
+            >
+                <OptionToggle
+                    selected=view
+                    on_select=move |target| view.set(target)
+                    options=VIEW_OPTIONS.to_vec()
+                    text=mass_text
+                />
+                <span>{summary}</span>
+            </DefaultChart>
+        }
     }
 }
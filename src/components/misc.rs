@@ -1,8 +1,13 @@
 use crate::{
-    elements::{default_chart::DefaultChart, tex_equation::TexEquation},
+    elements::{default_chart::DefaultChart, tex_equation::TexEquation, toggle_button::OptionToggle},
     utils::{
-        calculate_mass::*,
+        curve_features::curve_features,
+        expression::Formula,
+        evaluation_radius::EvaluationRadius,
+        galaxy_model::{GalaxyModel, HaloContraction, HaloProfile, HaloTruncation},
+        radial_grid::{RadialGrid, MAX_RADIUS_LIMITS},
         translation::{create_text_signal, Translation},
+        tully_fisher::normalization,
     },
 };
 use icondata as i;
@@ -10,6 +15,8 @@ use leptos::prelude::*;
 use leptos_chartistry::*;
 use leptos_icons::Icon;
 use std::vec;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlInputElement};
 
 // Vertical limit of chart
 const CHART_BOUND: f64 = 100.0;
@@ -17,26 +24,22 @@ const CHART_BOUND: f64 = 100.0;
 #[derive(PartialEq, Clone)]
 struct MassPoint {
     x: f64,
-    y_disk: f64,
-    y_halo_no_clamp: f64,
-    y_halo: f64,
+    // Mass of each component
+    ys: Vec<f64>,
+    ys_no_clamp: Vec<f64>,
 }
 
-// Convert units
-fn halo_factor() -> f64 {
-    (3.09 * 10.0_f64.powi(18)) / 2.0
-}
-
-fn disk_factor() -> f64 {
-    10.0_f64.powi(-1)
+// Radius for inputs and titles, empty if it does not exist
+fn format_radius(radius: Option<f64>) -> String {
+    radius.map(|radius| format!("{:.2}", radius)).unwrap_or_default()
 }
 
 #[component]
 fn MassBarChart(
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
-    iso_nfw: ReadSignal<bool>,
     text: ReadSignal<Translation>,
 ) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
     // Get mass-bar section of text
     let mass_bar_text: Memo<std::collections::HashMap<String, String>> = Memo::new(move |_| {
         text.get()
@@ -46,55 +49,63 @@ fn MassBarChart(
             .unwrap_or_default()
     });
 
+    let evaluation_radius = expect_context::<RwSignal<EvaluationRadius>>();
+    let radius = Memo::new(move |_| model.with(|model| evaluation_radius.get().resolve(model)));
+
     let mass_point: Memo<Vec<MassPoint>> = Memo::new(move |_| {
+        let radius = radius.get().unwrap_or(f64::NAN);
+        let ys_no_clamp: Vec<f64> = model.get().components()
+            .iter()
+            .map(|component| component.mass(radius))
+            .collect();
+
         vec![MassPoint {
             x: 0.0,
-            y_disk: {
-                let m_disk =
-                    mass_disk(30.0, slider_values.get().0, slider_values.get().1) * disk_factor();
-                if m_disk > CHART_BOUND {
-                    CHART_BOUND
-                } else {
-                    m_disk
-                }
-            },
-            y_halo_no_clamp: {
-                mass_halo(
-                    30.0,
-                    slider_values.get().2,
-                    slider_values.get().3,
-                    iso_nfw.get(),
-                ) * halo_factor()
-            },
-            y_halo: {
-                let m_halo = mass_halo(
-                    30.0,
-                    slider_values.get().2,
-                    slider_values.get().3,
-                    iso_nfw.get(),
-                ) * halo_factor();
-                if m_halo > CHART_BOUND {
-                    CHART_BOUND
-                } else {
-                    m_halo
-                }
-            },
+            ys: ys_no_clamp.iter().map(|&y| y.min(CHART_BOUND)).collect(),
+            ys_no_clamp,
         }]
     });
 
-    let series = Series::new(|data: &MassPoint| data.x)
-        .bar(
-            Bar::new(|data: &MassPoint| data.y_disk)
-                .with_name_dyn(create_text_signal(mass_bar_text, "Disk".to_string())),
-        )
-        .bar(
-            Bar::new(|data: &MassPoint| data.y_halo)
-                .with_name_dyn(create_text_signal(mass_bar_text, "Halo".to_string())),
-        )
-        .with_y_range(0.0, CHART_BOUND);
+    // Names of the components, the chart is only rebuilt if they change
+    let component_names = Memo::new(move |_| {
+        model.with(|model| {
+            model
+                .components()
+                .iter()
+                .map(|component| component.name())
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let translate = move |key: &'static str| {
+        move || mass_bar_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
 
-    view! {
-        <div id="mass_bar_chart">
+    // Invalid values are ignored, the input shows the current radius again
+    let on_radius = move |ev: Event| {
+        let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+        match input.value().parse::<f64>() {
+            Ok(value) if value > 0.0 && value.is_finite() => {
+                evaluation_radius.set(EvaluationRadius::Fixed(value.min(MAX_RADIUS_LIMITS.1)))
+            }
+            _ => input.set_value(&format_radius(radius.get_untracked())),
+        }
+    };
+
+    let chart = move || {
+        // One bar for each component
+        let mut series = Series::new(|data: &MassPoint| data.x);
+
+        for (i, name) in component_names.get().into_iter().enumerate() {
+            series = series.bar(
+                Bar::new(move |data: &MassPoint| data.ys.get(i).copied().unwrap_or(f64::NAN))
+                    .with_name_dyn(create_text_signal(mass_bar_text, name.to_string())),
+            );
+        }
+
+        let series = series.with_y_range(0.0, CHART_BOUND);
+
+        view! {
             <DefaultChart
                 y_label="Mass (10^10 * M☉)".to_string()
                 x_label="".to_string()
@@ -103,36 +114,68 @@ fn MassBarChart(
                 primary=false
                 label_text=mass_bar_text
             />
+        }
+    };
+
+    view! {
+        <div id="mass_bar_chart">
+            <div class="axis-controls">
+                <label>
+                    {translate("Radius")}
+                    <input
+                        type="number"
+                        min="0"
+                        step="any"
+                        prop:value=move || format_radius(radius.get())
+                        on:change=on_radius
+                    />
+                    "kpc"
+                </label>
+                <OptionToggle
+                    selected=evaluation_radius
+                    on_select=move |target| evaluation_radius.set(target)
+                    options=vec![
+                        (EvaluationRadius::Optical, "Optical radius"),
+                        (EvaluationRadius::R200, "r₂₀₀"),
+                    ]
+                    text=mass_bar_text
+                />
+            </div>
+            {chart}
             <div id="mass_bar_chart_values">
+                {move || {
+                    let masses = mass_point.get()[0].ys_no_clamp.clone();
+                    let total: f64 = masses.iter().sum();
+
+                    component_names
+                        .get()
+                        .into_iter()
+                        .zip(masses)
+                        .map(|(name, mass)| {
+                            view! {
+                                <span>
+                                    {format!(
+                                        "{}: {:.2} M☉ * 10^10 ({:.2}%)",
+                                        mass_bar_text
+                                            .get()
+                                            .get(name)
+                                            .cloned()
+                                            .unwrap_or(name.to_string()),
+                                        mass,
+                                        mass * 100.0 / total,
+                                    )}
+                                </span>
+                            }
+                        })
+                        .collect_view()
+                }}
                 <span>
                     {move || {
-                        format!(
-                            "{}: {:.2} M☉ * 10^10 ({:.2}%)",
-                            mass_bar_text
-                                .get()
-                                .get("Disk")
-                                .cloned()
-                                .unwrap_or("Disk".to_string()),
-                            mass_point.get()[0].y_disk,
-                            mass_point.get()[0].y_disk
-                                / (mass_point.get()[0].y_disk + mass_point.get()[0].y_halo_no_clamp)
-                                * 100.0,
-                        )
-                    }}
-                </span>
-                <span>
-                    {move || {
-                        format!(
-                            "{}: {:.2} M☉ * 10^10 ({:.2}%)",
-                            mass_bar_text
-                                .get()
-                                .get("Halo")
-                                .cloned()
-                                .unwrap_or("Halo".to_string()),
-                            mass_point.get()[0].y_halo_no_clamp,
-                            mass_point.get()[0].y_halo_no_clamp * 100.0
-                                / (mass_point.get()[0].y_disk + mass_point.get()[0].y_halo_no_clamp),
-                        )
+                        let label = translate("Total mass of the halo")();
+                        match model.with(|model| model.total_halo_mass()) {
+                            Some(mass) => format!("{}: {:.2} M☉ * 10^10", label, mass),
+                            None => format!("{}: {}", label, translate("unlimited, the halo is not truncated")()),
+                        }
                     }}
                 </span>
             </div>
@@ -140,14 +183,85 @@ fn MassBarChart(
     }
 }
 
+#[component]
+fn CurveFeatureTable(
+    text: ReadSignal<Translation>,
+) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let grid = expect_context::<RwSignal<RadialGrid>>();
+
+    // Get curve-features section of text
+    let features_text: Memo<std::collections::HashMap<String, String>> = Memo::new(move |_| {
+        text.get()
+            .0
+            .get("curve_features")
+            .cloned()
+            .unwrap_or_default()
+    });
+
+    let features = Memo::new(move |_| {
+        let max_radius = grid.with(|grid| grid.max_radius);
+        model.with(|model| curve_features(model, max_radius))
+    });
+
+    let translate = move |key: &'static str| {
+        move || features_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+    // Value of a feature, or a note if the chart does not contain it
+    let value = move |value: Option<String>| {
+        value.unwrap_or_else(|| translate("Not within the chart")())
+    };
+
+    view! {
+        <table id="curve_features">
+            <tr>
+                <th>{translate("Peak velocity")}</th>
+                <td>{move || value(features.get().peak.map(|(_, velocity)| format!("{:.1} km/s", velocity)))}</td>
+            </tr>
+            <tr>
+                <th>{translate("Radius of the peak")}</th>
+                <td>{move || value(features.get().peak.map(|(radius, _)| format!("{:.2} kpc", radius)))}</td>
+            </tr>
+            <tr>
+                <th>{translate("Flat velocity")}</th>
+                <td>{move || format!("{:.1} km/s", features.get().flat_velocity)}</td>
+            </tr>
+            <tr>
+                <th>{translate("Disk and halo velocity equal at")}</th>
+                <td>{move || value(features.get().crossing_radius.map(|radius| format!("{:.2} kpc", radius)))}</td>
+            </tr>
+            <tr>
+                <th>{translate("Halo holds half the mass from")}</th>
+                <td>{move || value(features.get().half_mass_radius.map(|radius| format!("{:.2} kpc", radius)))}</td>
+            </tr>
+        </table>
+    }
+}
+
 #[component]
 fn Equations(
     mode: ReadSignal<String>,
-    iso_nfw: ReadSignal<bool>,
     text: ReadSignal<Translation>,
 ) -> impl IntoView {
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
     let eq_text = Memo::new(move |_| text.get().0.get("equations").cloned().unwrap_or_default());
 
+    // TeX of the custom density functions, None while the built-in ones are used
+    let custom_tex = move |group: &'static str| {
+        Memo::new(move |_| {
+            model.with(|model| {
+                model.custom(group).map(|custom| {
+                    Formula::parse(&custom.formula)
+                        .map(|formula| formula.to_tex())
+                        .unwrap_or(custom.formula.clone())
+                })
+            })
+        })
+    };
+    let disk_tex = custom_tex("disk");
+    let halo_tex = custom_tex("halo");
+
     view! {
         <div id="equations">
             <Show when=move || { mode.get() == "velocity" }>
@@ -156,84 +270,322 @@ fn Equations(
                     text=eq_text
                     equation=r"v_{total}(r) = \sqrt{{v_{Disk}(r)}^2 + {v_{Halo}(r)}^2}".to_string()
                 />
-                <TexEquation
-                    label="Velocity of the disk".to_string()
-                    text=eq_text
-                    equation=r"v_{\text{Disk}}\left(r\right)=\sqrt{4\pi\cdot G\cdot\rho_{0_{D}}\cdot a_{D}\cdot{\gamma}^2\cdot\left(I_{0}\left(\gamma\right)K_{0}\left(\gamma\right)-I_{1}\left(\gamma\right)\cdot K_{1}\left(\gamma\right)\right)}"
-                        .to_string()
-                />
-                <TexEquation
-                    label="Gamma".to_string()
-                    text=eq_text
-                    equation=r"\gamma=\frac{r}{2\cdot a_{D}}".to_string()
-                />
+                <Show
+                    when=move || disk_tex.get().is_none()
+                    fallback=move || {
+                        view! {
+                            <TexEquation
+                                label="Velocity of the disk, from the numerically integrated potential"
+                                    .to_string()
+                                text=eq_text
+                                equation=r"v_{\text{Disk}}\left(r\right)=\sqrt{r\cdot\frac{d\Phi_{\text{Disk}}}{dr}},\quad\Phi_{\text{Disk}}\left(r\right)=-4G\int_{0}^{\infty}\frac{\rho_{\text{Disk}}\left(r'\right)\cdot r'}{r+r'}\cdot K\left(\frac{2\sqrt{r\cdot r'}}{r+r'}\right)dr'"
+                                    .to_string()
+                            />
+                        }
+                    }
+                >
+                    <TexEquation
+                        label="Velocity of the disk".to_string()
+                        text=eq_text
+                        equation=r"v_{\text{Disk}}\left(r\right)=\sqrt{4\pi\cdot G\cdot\rho_{0_{D}}\cdot a_{D}\cdot{\gamma}^2\cdot\left(I_{0}\left(\gamma\right)K_{0}\left(\gamma\right)-I_{1}\left(\gamma\right)\cdot K_{1}\left(\gamma\right)\right)}"
+                            .to_string()
+                    />
+                    <TexEquation
+                        label="Gamma".to_string()
+                        text=eq_text
+                        equation=r"\gamma=\frac{r}{2\cdot a_{D}}".to_string()
+                    />
+                </Show>
                 <TexEquation
                     label="Velocity of the halo".to_string()
                     text=eq_text
                     equation=r"v_{\text{Halo}}\left(r\right)=\sqrt{\frac{G\cdot M_{\text{Halo}}\left(r\right)}{r}}"
                         .to_string()
                 />
+                <Show when=move || model.with(|model| model.halo.axis_ratio != 1.0 && model.halo.custom.is_none())>
+                    <TexEquation
+                        label="Velocity of an ellipsoidal halo in its midplane, with the axis ratio q".to_string()
+                        text=eq_text
+                        equation=r"v_{\text{Halo}}\left(R\right)=\sqrt{4\pi G\cdot q\cdot\int_{0}^{R}\frac{\rho_{\text{Halo}}\left(m\right)\cdot m^2}{\sqrt{R^2-e^2\cdot m^2}}\,dm},\quad e^2=1-q^2"
+                            .to_string()
+                    />
+                </Show>
             </Show>
             <Show when=move || { mode.get() == "mass" }>
-                <TexEquation
-                    label="Mass of the disk, where d (width) = 0.5".to_string()
-                    text=eq_text
-                    equation=r"M_{\text{Disk}}\left(r\right)=2\pi\cdot\rho_{0_{D}}\cdot d\cdot a_D\cdot\left(a_D-\left(r+a_D\right)\cdot e^{-\frac{r}{a_D}}\right)"
-                        .to_string()
-                />
                 <Show
-                    when=move || { !iso_nfw.get() }
+                    when=move || disk_tex.get().is_none()
                     fallback=move || {
                         view! {
                             <TexEquation
-                                label="Mass of the halo with a density function according to an isothermal gas sphere"
-                                    .to_string()
+                                label="Mass of the disk, integrated numerically".to_string()
                                 text=eq_text
-                                equation=r"M_{\text{Halo}}\left(r\right)=4\pi\cdot\rho_{0_{H}}\cdot{a_{H}}^2\cdot\left(r-a_{H}\cdot\arctan\left(\frac{r}{a_{H}}\right)\right)"
+                                equation=r"M_{\text{Disk}}\left(r\right)=2\pi\cdot d\cdot\int_{0}^{r}\rho_{\text{Disk}}\left(r'\right)\cdot r'\,dr'"
                                     .to_string()
                             />
                         }
                     }
                 >
                     <TexEquation
-                        label="Mass of the halo with a density function according to Navarro, Frenk, and White"
-                            .to_string()
+                        label="Mass of the disk, where d (width) = 0.5".to_string()
                         text=eq_text
-                        equation=r"M_{\text{Halo}}\left(r\right)=4\pi\cdot\rho_{0_{H}}\cdot{a_{H}}^3\cdot\left(\ln\left(\frac{r+a_{H}}{a_{H}}\right)-\frac{r}{r+a_{H}}\right)"
+                        equation=r"M_{\text{Disk}}\left(r\right)=2\pi\cdot\rho_{0_{D}}\cdot d\cdot a_D\cdot\left(a_D-\left(r+a_D\right)\cdot e^{-\frac{r}{a_D}}\right)"
                             .to_string()
                     />
                 </Show>
-            </Show>
-            <Show when=move || { mode.get() == "density" }>
-                <TexEquation
-                    label="Density of the disk".to_string()
-                    text=eq_text
-                    equation=r"\rho_{\text{Disk}}\left(r\right)=\rho_{0_{D}}\cdot{e^{-{\frac{r}{a_{D}}}}}"
-                        .to_string()
-                />
                 <Show
-                    when=move || { !iso_nfw.get() }
+                    when=move || halo_tex.get().is_none()
                     fallback=move || {
                         view! {
                             <TexEquation
-                                label="Density of the halo according to an isothermal gas sphere"
-                                    .to_string()
+                                label="Mass of the halo, integrated numerically".to_string()
                                 text=eq_text
-                                equation=r"\rho_{\text{Halo}}\left(r\right)=\frac{\rho_{0_{H}}}{1+\left(\frac{r}{a_{H}}\right)^2}"
+                                equation=r"M_{\text{Halo}}\left(r\right)=4\pi\cdot\int_{0}^{r}\rho_{\text{Halo}}\left(r'\right)\cdot r'^2\,dr'"
                                     .to_string()
                             />
                         }
                     }
                 >
+                    <Show
+                        when=move || { model.with(|model| model.halo.profile == HaloProfile::Nfw) }
+                        fallback=move || {
+                            view! {
+                                <TexEquation
+                                    label="Mass of the halo with a density function according to an isothermal gas sphere"
+                                        .to_string()
+                                    text=eq_text
+                                    equation=r"M_{\text{Halo}}\left(r\right)=4\pi\cdot\rho_{0_{H}}\cdot{a_{H}}^2\cdot\left(r-a_{H}\cdot\arctan\left(\frac{r}{a_{H}}\right)\right)"
+                                        .to_string()
+                                />
+                            }
+                        }
+                    >
+                        <TexEquation
+                            label="Mass of the halo with a density function according to Navarro, Frenk, and White"
+                                .to_string()
+                            text=eq_text
+                            equation=r"M_{\text{Halo}}\left(r\right)=4\pi\cdot\rho_{0_{H}}\cdot{a_{H}}^3\cdot\left(\ln\left(\frac{r+a_{H}}{a_{H}}\right)-\frac{r}{r+a_{H}}\right)"
+                                .to_string()
+                        />
+                    </Show>
+                </Show>
+                <Show when=move || { model.with(|model| model.halo.truncation == HaloTruncation::Virial) }>
+                    <TexEquation
+                        label="Halo truncated at r₂₀₀".to_string()
+                        text=eq_text
+                        equation=r"M_{\text{Halo}}\left(r>r_{200}\right)=M_{\text{Halo}}\left(r_{200}\right)".to_string()
+                    />
+                </Show>
+                <Show when=move || { model.with(|model| model.halo.truncation == HaloTruncation::Exponential) }>
+                    <TexEquation
+                        label="Density of the halo beyond r₂₀₀, exponentially cut off".to_string()
+                        text=eq_text
+                        equation=r"\rho_{\text{Halo}}\left(r>r_{200}\right)=\rho_{\text{Halo}}\left(r\right)\cdot e^{-\frac{r-r_{200}}{0.1\cdot r_{200}}}".to_string()
+                    />
+                </Show>
+                <Show when=move || model.with(|model| model.halo.axis_ratio != 1.0 && model.halo.custom.is_none())>
+                    <TexEquation
+                        label="Mass of an ellipsoidal halo within the ellipsoid of major axis r".to_string()
+                        text=eq_text
+                        equation=r"M_{\text{Halo}}\left(r,q\right)=q\cdot M_{\text{Halo}}\left(r\right)".to_string()
+                    />
+                </Show>
+                <Show when=move || { model.with(|model| model.halo.contraction == HaloContraction::Blumenthal) }>
+                    <TexEquation
+                        label="Adiabatic contraction after Blumenthal, a shell of dark matter moves from r_i to r_f".to_string()
+                        text=eq_text
+                        equation=r"r_f\cdot\left(M_{\text{Disk}}\left(r_f\right)+M_{\text{Halo}}\left(r_i\right)\right)=r_i\cdot\left(M_{\text{Halo}}\left(r_i\right)+M_{b}\left(r_i\right)\right),\quad M_{\text{Halo},c}\left(r_f\right)=M_{\text{Halo}}\left(r_i\right)"
+                            .to_string()
+                    />
+                </Show>
+                <Show when=move || { model.with(|model| model.halo.contraction == HaloContraction::Gnedin) }>
                     <TexEquation
-                        label="Density of the halo according to Navarro, Frenk, and White"
+                        label="Adiabatic contraction after Gnedin, with the orbit-averaged radii r̄".to_string()
+                        text=eq_text
+                        equation=r"r_f\cdot\left(M_{\text{Disk}}\left(\bar{r}_f\right)+M_{\text{Halo}}\left(\bar{r}_i\right)\right)=r_i\cdot\left(M_{\text{Halo}}\left(\bar{r}_i\right)+M_{b}\left(\bar{r}_i\right)\right),\quad\bar{r}=0.85\cdot r_{200}\cdot\left(\frac{r}{r_{200}}\right)^{0.8}"
                             .to_string()
+                    />
+                </Show>
+                <Show when=move || { model.with(|model| model.halo.contraction != HaloContraction::None) }>
+                    <TexEquation
+                        label="Baryons before the contraction, distributed like the halo within r₂₀₀".to_string()
                         text=eq_text
-                        equation=r"\rho_{\text{Halo}}\left(r\right)=\frac{\rho_{0_{H}}}{\frac{r}{a_{H}}\left(1+\frac{r}{a_{H}}\right)^2}"
+                        equation=r"M_{b}\left(r\right)=M_{\text{Disk}}\left(r_{200}\right)\cdot\min\left(\frac{M_{\text{Halo}}\left(r\right)}{M_{\text{Halo}}\left(r_{200}\right)},1\right)"
                             .to_string()
                     />
                 </Show>
             </Show>
+            <Show when=move || { mode.get() == "potential" }>
+                <TexEquation
+                    label="Total potential".to_string()
+                    text=eq_text
+                    equation=r"\Phi\left(r\right)=\Phi_{\text{Disk}}\left(r\right)+\Phi_{\text{Halo}}\left(r\right)".to_string()
+                />
+                <TexEquation
+                    label="Escape velocity".to_string()
+                    text=eq_text
+                    equation=r"v_{\text{esc}}\left(r\right)=\sqrt{-2\Phi\left(r\right)}".to_string()
+                />
+                <TexEquation
+                    label="Potential of the disk in its plane".to_string()
+                    text=eq_text
+                    equation=r"\Phi_{\text{Disk}}\left(r\right)=-\pi\cdot G\cdot\rho_{0_{D}}\cdot r\cdot\left(I_{0}\left(\gamma\right)K_{1}\left(\gamma\right)-I_{1}\left(\gamma\right)K_{0}\left(\gamma\right)\right)"
+                        .to_string()
+                />
+                <TexEquation
+                    label="Potential of the halo, zero at r₀ (infinity for NFW, otherwise 200 kpc)".to_string()
+                    text=eq_text
+                    equation=r"\Phi_{\text{Halo}}\left(r\right)=-G\cdot\int_{r}^{r_{0}}\frac{M_{\text{Halo}}\left(r'\right)}{r'^2}\,dr'".to_string()
+                />
+            </Show>
+            <Show when=move || { mode.get() == "rotation" }>
+                <TexEquation
+                    label="Angular velocity".to_string()
+                    text=eq_text
+                    equation=r"\Omega\left(r\right)=\frac{v_{total}\left(r\right)}{r}".to_string()
+                />
+                <TexEquation
+                    label="Orbital period".to_string()
+                    text=eq_text
+                    equation=r"T\left(r\right)=\frac{2\pi\cdot r}{v_{total}\left(r\right)}=\frac{2\pi}{\Omega\left(r\right)}".to_string()
+                />
+            </Show>
+            <Show when=move || { mode.get() == "orbit" }>
+                <TexEquation
+                    label="Acceleration in the plane of the galaxy".to_string()
+                    text=eq_text
+                    equation=r"\vec{a}\left(\vec{r}\right)=-\frac{{v_{total}\left(r\right)}^2}{r}\cdot\frac{\vec{r}}{r}".to_string()
+                />
+                <TexEquation
+                    label="Leapfrog step".to_string()
+                    text=eq_text
+                    equation=r"\vec{v}_{n+\frac{1}{2}}=\vec{v}_{n}+\frac{\Delta t}{2}\cdot\vec{a}_{n},\quad\vec{r}_{n+1}=\vec{r}_{n}+\Delta t\cdot\vec{v}_{n+\frac{1}{2}},\quad\vec{v}_{n+1}=\vec{v}_{n+\frac{1}{2}}+\frac{\Delta t}{2}\cdot\vec{a}_{n+1}".to_string()
+                />
+                <TexEquation
+                    label="Energy per mass".to_string()
+                    text=eq_text
+                    equation=r"E=\frac{v^2}{2}+\Phi\left(r\right)".to_string()
+                />
+                <TexEquation
+                    label="Energy error, relative to the energy scale of the orbit".to_string()
+                    text=eq_text
+                    equation=r"\frac{\left|E-E_0\right|}{\max\left(\frac{{v_0}^2}{2}+\left|\Phi\left(r_0\right)\right|,\,{v_{total}\left(r_0\right)}^2\right)}".to_string()
+                />
+                <TexEquation
+                    label="Time step, from the circular orbit at the start".to_string()
+                    text=eq_text
+                    equation=r"\Delta t=\max\left(\frac{1}{500}\cdot\frac{2\pi r_0}{v_{total}\left(r_0\right)},\,\frac{T}{20000}\right)".to_string()
+                />
+            </Show>
+            <Show when=move || { mode.get() == "inversion" }>
+                <TexEquation
+                    label="Dynamical mass from the observed velocity".to_string()
+                    text=eq_text
+                    equation=r"M_{\text{dyn}}\left(r\right)=\frac{{v_{\text{obs}}\left(r\right)}^2\cdot r}{G}".to_string()
+                />
+                <TexEquation
+                    label="Implied dark matter".to_string()
+                    text=eq_text
+                    equation=r"M_{\text{DM}}\left(r\right)=M_{\text{dyn}}\left(r\right)-M_{\text{Disk}}\left(r\right)".to_string()
+                />
+                <TexEquation
+                    label="Implied spherical density".to_string()
+                    text=eq_text
+                    equation=r"\rho_{\text{DM}}\left(r\right)=\frac{1}{4\pi\cdot r^2}\cdot\frac{dM_{\text{DM}}}{dr}".to_string()
+                />
+            </Show>
+            <Show when=move || { mode.get() == "rar" }>
+                <TexEquation
+                    label="Centripetal accelerations".to_string()
+                    text=eq_text
+                    equation=r"g_{\text{obs}}=\frac{{v_{\text{obs}}}^2}{r},\quad g_{\text{bar}}=\frac{{v_{\text{Disk}}}^2}{r}".to_string()
+                />
+                <TexEquation
+                    label="Radial acceleration relation".to_string()
+                    text=eq_text
+                    equation=r"g_{\text{obs}}=\frac{g_{\text{bar}}}{1-e^{-\sqrt{g_{\text{bar}}/g_{\dagger}}}},\quad g_{\dagger}=1.2\cdot10^{-10}\,\text{m}/\text{s}^2".to_string()
+                />
+            </Show>
+            <Show when=move || { mode.get() == "tully_fisher" }>
+                <TexEquation
+                    label="Baryonic Tully-Fisher relation, fitted to the sample".to_string()
+                    text=eq_text
+                    equation=format!(
+                        r"M_{{\text{{bar}}}}=A\cdot{{v_{{f}}}}^4,\quad A=10^{{\left\langle\log M_{{\text{{bar}}}}-4\log v_{{f}}\right\rangle}}={:.1}\,\text{{M}}_{{\odot}}\cdot\left(\text{{km}}/\text{{s}}\right)^{{-4}}",
+                        normalization() * 1.0e10,
+                    )
+                />
+                <TexEquation
+                    label="Baryonic mass of the model, the whole disk".to_string()
+                    text=eq_text
+                    equation=r"M_{\text{bar}}=M_{\text{Disk}}\left(20\cdot a_{D}\right)".to_string()
+                />
+                <TexEquation
+                    label="Flat velocity, averaged from one to two optical radii".to_string()
+                    text=eq_text
+                    equation=r"v_{f}=\left\langle v_{total}\left(r\right)\right\rangle_{3.2\cdot a_{D}\le r\le 6.4\cdot a_{D}}".to_string()
+                />
+            </Show>
+            <Show when=move || { mode.get() == "density" }>
+                {move || match disk_tex.get() {
+                    Some(tex) => view! {
+                        <TexEquation
+                            label="Custom density of the disk".to_string()
+                            text=eq_text
+                            equation=format!(r"\rho_{{\text{{Disk}}}}\left(r\right)={}", tex)
+                        />
+                    }
+                    .into_any(),
+                    None => view! {
+                        <TexEquation
+                            label="Density of the disk".to_string()
+                            text=eq_text
+                            equation=r"\rho_{\text{Disk}}\left(r\right)=\rho_{0_{D}}\cdot{e^{-{\frac{r}{a_{D}}}}}"
+                                .to_string()
+                        />
+                    }
+                    .into_any(),
+                }}
+                <Show
+                    when=move || halo_tex.get().is_none()
+                    fallback=move || {
+                        move || {
+                            view! {
+                                <TexEquation
+                                    label="Custom density of the halo".to_string()
+                                    text=eq_text
+                                    equation=format!(
+                                        r"\rho_{{\text{{Halo}}}}\left(r\right)={}",
+                                        halo_tex.get().unwrap_or_default(),
+                                    )
+                                />
+                            }
+                        }
+                    }
+                >
+                    <Show
+                        when=move || { model.with(|model| model.halo.profile == HaloProfile::Nfw) }
+                        fallback=move || {
+                            view! {
+                                <TexEquation
+                                    label="Density of the halo according to an isothermal gas sphere"
+                                        .to_string()
+                                    text=eq_text
+                                    equation=r"\rho_{\text{Halo}}\left(r\right)=\frac{\rho_{0_{H}}}{1+\left(\frac{r}{a_{H}}\right)^2}"
+                                        .to_string()
+                                />
+                            }
+                        }
+                    >
+                        <TexEquation
+                            label="Density of the halo according to Navarro, Frenk, and White"
+                                .to_string()
+                            text=eq_text
+                            equation=r"\rho_{\text{Halo}}\left(r\right)=\frac{\rho_{0_{H}}}{\frac{r}{a_{H}}\left(1+\frac{r}{a_{H}}\right)^2}"
+                                .to_string()
+                        />
+                    </Show>
+                </Show>
+            </Show>
         </div>
     }
 }
@@ -241,14 +593,16 @@ fn Equations(
 #[component]
 pub fn Misc(
     mode: ReadSignal<String>,
-    iso_nfw: ReadSignal<bool>,
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
     text: ReadSignal<Translation>,
 ) -> impl IntoView {
     // Get misc section of text
     let misc_text: Memo<std::collections::HashMap<String, String>> = Memo::new(move |_| text.get().0.get("misc").cloned().unwrap_or_default());
 
-    // Possible values = [mass_details, equations]
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let evaluation_radius = expect_context::<RwSignal<EvaluationRadius>>();
+    let details_radius = Memo::new(move |_| model.with(|model| evaluation_radius.get().resolve(model)));
+
+    // Possible values = [mass_details, curve_features, equations]
     let (tab_selected, set_tab_selected) = signal("mass_details".to_string());
 
     view! {
@@ -256,12 +610,25 @@ pub fn Misc(
             <div class="tab_selector">
                 <button on:click=move |_| {
                     set_tab_selected.set("mass_details".to_string());
+                }>
+                    {move || format!(
+                        "{} {} kpc",
+                        misc_text
+                            .get()
+                            .get("Details for mass at")
+                            .cloned()
+                            .unwrap_or("Details for mass at".to_string()),
+                        format_radius(details_radius.get()),
+                    )}
+                </button>
+                <button on:click=move |_| {
+                    set_tab_selected.set("curve_features".to_string());
                 }>
                     {move || misc_text
                         .get()
-                        .get("Details for mass at 30kpc")
+                        .get("Curve features")
                         .cloned()
-                        .unwrap_or("Details for mass at 30kpc".to_string())
+                        .unwrap_or("Curve features".to_string())
                     }
                 </button>
                 <button on:click=move |_| {
@@ -289,10 +656,13 @@ pub fn Misc(
             </div>
             <div class="tab_elements">
                 <Show when=move || { tab_selected.get() == "mass_details" }>
-                    <MassBarChart slider_values=slider_values iso_nfw=iso_nfw text=text />
+                    <MassBarChart text=text />
+                </Show>
+                <Show when=move || { tab_selected.get() == "curve_features" }>
+                    <CurveFeatureTable text=text />
                 </Show>
                 <Show when=move || { tab_selected.get() == "equations" }>
-                    <Equations mode=mode iso_nfw=iso_nfw text=text />
+                    <Equations mode=mode text=text />
                 </Show>
             </div>
         </div>
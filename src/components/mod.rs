@@ -1,5 +1,12 @@
 pub mod velocity_chart;
 pub mod mass_chart;
 pub mod density_chart;
+pub mod potential_chart;
+pub mod rotation_chart;
+pub mod galaxy_animation;
+pub mod orbit_chart;
+pub mod inversion_chart;
+pub mod rar_chart;
+pub mod tully_fisher_chart;
 pub mod inputs;
 pub mod misc;
\ No newline at end of file
@@ -0,0 +1,170 @@
+use leptos::prelude::*;
+use leptos_chartistry::*;
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlInputElement};
+use crate::{
+    elements::{default_chart::DefaultChart, toggle_button::OptionToggle}, utils::{
+        galaxy_model::GalaxyModel,
+        mass_component::total_velocity,
+        orbit::{integrate_orbit, OrbitSample, OrbitSettings, DURATION_LIMITS, VELOCITY_LIMITS},
+        radial_grid::MAX_RADIUS_LIMITS,
+        translation::{create_text_signal, Translation}
+    }
+};
+
+// Quantity shown by the orbit chart
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OrbitView {
+    // Path of the star seen from above
+    #[default]
+    Path,
+    // Relative energy error over time
+    EnergyError,
+}
+
+#[component]
+pub fn OrbitChart(
+    text: ReadSignal<Translation>,
+    view: RwSignal<OrbitView>,
+    settings: RwSignal<OrbitSettings>,
+) -> impl IntoView {
+    // Get orbit section of text
+    let orbit_text: Memo<HashMap<String, String>> =
+        Memo::new(move |_| text.get().0.get("orbit").cloned().unwrap_or_default());
+
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
+    let orbit = Memo::new(move |_| {
+        let settings = settings.get();
+        model.with(|model| integrate_orbit(&model.components(), &settings))
+    });
+
+    let translate = move |key: &'static str| {
+        move || orbit_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    // Number input for one value of the settings; invalid values are ignored
+    let setting_input = move |key: &'static str,
+                              unit: &'static str,
+                              (min, max): (f64, f64),
+                              get: fn(&OrbitSettings) -> f64,
+                              set: fn(&mut OrbitSettings, f64)| {
+        let on_change = move |ev: Event| {
+            let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+            match input.value().parse::<f64>() {
+                Ok(value) if value.is_finite() => settings.update(|settings| set(settings, value.clamp(min, max))),
+                _ => input.set_value(&settings.with_untracked(get).to_string()),
+            }
+        };
+        view! {
+            <label>
+                {translate(key)}
+                <input
+                    type="number"
+                    min=min.to_string()
+                    max=max.to_string()
+                    step="any"
+                    prop:value=move || settings.with(get).to_string()
+                    on:change=on_change
+                />
+                {unit}
+            </label>
+        }
+    };
+
+    // Start on the circular orbit at the initial radius
+    let set_circular = move |_| {
+        let radius = settings.get_untracked().radius;
+        let velocity = model.with_untracked(|model| total_velocity(&model.components(), radius));
+        settings.update(|settings| {
+            settings.radial_velocity = 0.0;
+            settings.tangential_velocity = velocity;
+        });
+    };
+
+    move || {
+        let series = match view.get() {
+            OrbitView::Path => {
+                // Equal ranges on both axes around the center
+                let extent = orbit
+                    .get()
+                    .iter()
+                    .map(|sample| sample.x.abs().max(sample.y.abs()))
+                    .fold(0.0_f64, f64::max)
+                    * 1.05;
+                Series::new(|sample: &OrbitSample| sample.x)
+                    .line(
+                        Line::new(|sample: &OrbitSample| sample.y)
+                            .with_name_dyn(create_text_signal(orbit_text, "Orbit".to_string()))
+                            .with_width(1.5),
+                    )
+                    .with_x_range(-extent, extent)
+                    .with_y_range(-extent, extent)
+            }
+            OrbitView::EnergyError => Series::new(|sample: &OrbitSample| sample.time)
+                .line(
+                    Line::new(|sample: &OrbitSample| sample.energy_error)
+                        .with_name_dyn(create_text_signal(orbit_text, "Energy error".to_string()))
+                        .with_width(3.0),
+                )
+                .with_min_y(RwSignal::new(Some(0.0))),
+        };
+        let (x_label, y_label) = match view.get() {
+            OrbitView::Path => ("x (kpc)", "y (kpc)"),
+            OrbitView::EnergyError => ("Time (Myr)", "Relative energy error"),
+        };
+
+        view! {
+            <DefaultChart
+                y_label=y_label.to_string()
+                x_label=x_label.to_string()
+                series=series
+                data=orbit
+                primary=true
+                label_text=orbit_text
+            >
+                <OptionToggle
+                    selected=view
+                    on_select=move |target| view.set(target)
+                    options=vec![
+                        (OrbitView::Path, "Orbit"),
+                        (OrbitView::EnergyError, "Energy error"),
+                    ]
+                    text=orbit_text
+                />
+                {setting_input(
+                    "Initial radius",
+                    "kpc",
+                    MAX_RADIUS_LIMITS,
+                    |settings| settings.radius,
+                    |settings, value| settings.radius = value,
+                )}
+                {setting_input(
+                    "Radial velocity",
+                    "km/s",
+                    VELOCITY_LIMITS,
+                    |settings| settings.radial_velocity,
+                    |settings, value| settings.radial_velocity = value,
+                )}
+                {setting_input(
+                    "Tangential velocity",
+                    "km/s",
+                    VELOCITY_LIMITS,
+                    |settings| settings.tangential_velocity,
+                    |settings, value| settings.tangential_velocity = value,
+                )}
+                {setting_input(
+                    "Duration",
+                    "Myr",
+                    DURATION_LIMITS,
+                    |settings| settings.duration,
+                    |settings, value| settings.duration = value,
+                )}
+                <button class="axis-toggle" on:click=set_circular>
+                    {translate("Circular orbit")}
+                </button>
+            </DefaultChart>
+        }
+    }
+}
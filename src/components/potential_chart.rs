@@ -0,0 +1,147 @@
+use leptos::prelude::*;
+use leptos_chartistry::*;
+use std::collections::HashMap;
+use crate::{
+    elements::{default_chart::DefaultChart, toggle_button::OptionToggle}, utils::{
+        calculate_potential::ISO_REFERENCE_RADIUS,
+        galaxy_model::{GalaxyModel, HaloProfile},
+        mass_component::{total_velocity, MassComponent},
+        radial_grid::RadialGrid,
+        translation::{create_text_signal, Translation}
+    }
+};
+
+// Quantity shown by the potential chart
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PotentialView {
+    #[default]
+    Potential,
+    // Escape velocity compared to the circular velocity
+    EscapeVelocity,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+struct PotentialPoint {
+    x: f64,
+    // Potential of each component and the total, or escape and circular velocity
+    ys: Vec<f64>
+}
+
+fn get_potential_points(components: &[Box<dyn MassComponent>], grid: &RadialGrid) -> Vec<PotentialPoint> {
+    // Potentials add up, the total is the last curve
+    grid.sample(|x| {
+        let mut ys: Vec<f64> = components.iter().map(|component| component.potential(x)).collect();
+        ys.push(ys.iter().sum());
+        ys
+    })
+    .into_iter()
+    .map(|(x, ys)| PotentialPoint { x, ys })
+    .collect()
+}
+
+fn get_escape_points(components: &[Box<dyn MassComponent>], grid: &RadialGrid) -> Vec<PotentialPoint> {
+    grid.sample(|x| {
+        let potential: f64 = components.iter().map(|component| component.potential(x)).sum();
+        // Bound orbits need a negative potential
+        let escape = if potential < 0.0 { (-2.0 * potential).sqrt() } else { f64::NAN };
+        vec![escape, total_velocity(components, x)]
+    })
+    .into_iter()
+    .map(|(x, ys)| PotentialPoint { x, ys })
+    .collect()
+}
+
+#[component]
+pub fn PotentialChart(
+    text: ReadSignal<Translation>,
+    view: RwSignal<PotentialView>,
+) -> impl IntoView {
+    // Get potential section of text
+    let potential_text: Memo<HashMap<String, String>> =
+        Memo::new(move |_| text.get().0.get("potential").cloned().unwrap_or_default());
+
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let grid = expect_context::<RwSignal<RadialGrid>>();
+
+    let potential_points = Memo::new(move |_| {
+        let components = model.get().components();
+        grid.with(|grid| match view.get() {
+            PotentialView::Potential => get_potential_points(&components, grid),
+            PotentialView::EscapeVelocity => get_escape_points(&components, grid),
+        })
+    });
+
+    // Names of the curves, the chart is only rebuilt if they change
+    let curve_names = Memo::new(move |_| match view.get() {
+        PotentialView::Potential => {
+            let mut names: Vec<&'static str> = model.with(|model| {
+                model.components().iter().map(|component| component.name()).collect()
+            });
+            names.push("Total");
+            names
+        }
+        PotentialView::EscapeVelocity => vec!["Escape velocity", "Circular velocity"],
+    });
+
+    // The potential of the isothermal sphere and of custom halos has its zero at a finite radius
+    let finite_reference = Memo::new(move |_| {
+        model.with(|model| model.halo.profile == HaloProfile::Isothermal || model.custom("halo").is_some())
+    });
+    let reference_note = move || {
+        finite_reference.get().then(|| {
+            let key = "The potential of the halo is set to zero at";
+            let note = potential_text.get().get(key).cloned().unwrap_or(key.to_string());
+            view! { <span>{format!("{} {} kpc", note, ISO_REFERENCE_RADIUS)}</span> }
+        })
+    };
+
+    let max_radius = Memo::new(move |_| grid.with(|grid| grid.max_radius));
+
+    move || {
+        // One line for each curve, the components of the potential are drawn thinner
+        let current_view = view.get();
+        let names = curve_names.get();
+        let mut series = Series::new(|data: &PotentialPoint| data.x);
+
+        for (i, name) in names.iter().copied().enumerate() {
+            let width = match current_view {
+                PotentialView::Potential if i + 1 < names.len() => 1.5,
+                _ => 3.0,
+            };
+            series = series.line(Line::new(move |data: &PotentialPoint| data.ys.get(i).copied().unwrap_or(f64::NAN))
+                .with_name_dyn(
+                    create_text_signal(potential_text, name.to_string())
+                )
+                .with_width(width)
+            );
+        }
+
+        let series = series.with_x_range(0.0, max_radius.get());
+        let y_label = match current_view {
+            PotentialView::Potential => "Potential ((km/s)²)",
+            PotentialView::EscapeVelocity => "Velocity (km/s)",
+        };
+
+        view! {
+            <DefaultChart
+                y_label=y_label.to_string()
+                x_label="Radius (kpc)".to_string()
+                series=series
+                data=potential_points
+                primary=true
+                label_text=potential_text
+            >
+                <OptionToggle
+                    selected=view
+                    on_select=move |target| view.set(target)
+                    options=vec![
+                        (PotentialView::Potential, "Potential"),
+                        (PotentialView::EscapeVelocity, "Escape velocity"),
+                    ]
+                    text=potential_text
+                />
+                {reference_note}
+            </DefaultChart>
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use leptos::prelude::*;
+use leptos_chartistry::*;
+use std::collections::HashMap;
+use crate::{
+    elements::default_chart::{apply_y_range, DefaultChart}, utils::{
+        axis_settings::AxisSettings,
+        galaxy_model::GalaxyModel,
+        observed::ngc_3198,
+        rar::{accelerations, rar_fit, AccelerationPoint},
+        reference_line::{positive_range, with_reference_line},
+        translation::{create_text_signal, Translation}
+    }
+};
+
+// Range of the lines around the samples, in decades
+const LINE_MARGIN: f64 = 0.5;
+
+#[derive(PartialEq, Clone, Debug)]
+struct RarPoint {
+    x: f64,
+    // Sample, 1:1 line and fitting function
+    ys: Vec<f64>
+}
+
+// Samples and the lines through the range of their baryonic accelerations, sorted by it
+fn get_rar_points(samples: &[AccelerationPoint]) -> Vec<(f64, Vec<f64>)> {
+    let Some((min, max)) = positive_range(samples.iter().map(|sample| sample.baryonic)) else {
+        return Vec::new();
+    };
+    let margin = 10.0_f64.powf(LINE_MARGIN);
+    with_reference_line(
+        (min / margin, max * margin),
+        |g| vec![f64::NAN, g, rar_fit(g)],
+        samples
+            .iter()
+            .map(|sample| (sample.baryonic, vec![sample.observed, sample.baryonic, rar_fit(sample.baryonic)])),
+    )
+}
+
+#[component]
+pub fn RarChart(
+    text: ReadSignal<Translation>,
+    axes: RwSignal<AxisSettings>,
+) -> impl IntoView {
+    // Get rar section of text
+    let rar_text: Memo<HashMap<String, String>> =
+        Memo::new(move |_| text.get().0.get("rar").cloned().unwrap_or_default());
+
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+
+    let rar_points = Memo::new(move |_| {
+        let samples = model.with(|model| {
+            let [disk, _] = model.disk_and_halo();
+            accelerations(&ngc_3198(), disk.as_ref())
+        });
+        let axes = axes.get();
+
+        // Positions on the (possibly logarithmic) axes, clipped to the limits
+        axes.clipped_positions(get_rar_points(&samples))
+            .into_iter()
+            .map(|(x, ys)| RarPoint { x, ys })
+            .collect::<Vec<_>>()
+    });
+
+    let translate = move |key: &'static str| {
+        move || rar_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    move || {
+        let series = Series::new(|data: &RarPoint| data.x)
+            // Only the markers of the samples, without connecting lines
+            .line(
+                Line::new(|data: &RarPoint| data.ys[0])
+                    .with_name_dyn(create_text_signal(rar_text, "Sample Values (NGC 3198)".to_string()))
+                    .with_width(0.0)
+                    .with_marker(RwSignal::new(Marker::from_shape(MarkerShape::Circle).with_scale(1.5))),
+            )
+            .line(
+                Line::new(|data: &RarPoint| data.ys[1])
+                    .with_name_dyn(create_text_signal(rar_text, "1:1 line (no dark matter)".to_string()))
+                    .with_width(1.5),
+            )
+            .line(
+                Line::new(|data: &RarPoint| data.ys[2])
+                    .with_name_dyn(create_text_signal(rar_text, "Radial acceleration relation".to_string()))
+                    .with_width(1.5),
+            );
+        let series = apply_y_range(series, &axes.get());
+
+        view! {
+            <DefaultChart
+                y_label="Observed acceleration (m/s²)".to_string()
+                x_label="Baryonic acceleration (m/s²)".to_string()
+                series=series
+                data=rar_points
+                primary=true
+                label_text=rar_text
+                axes=axes
+            >
+                <span>{translate("The baryonic acceleration is that of the modelled disk")}</span>
+            </DefaultChart>
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use leptos::prelude::*;
+use leptos_chartistry::*;
+use std::{collections::HashMap, f64::consts::PI};
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlInputElement};
+use crate::{
+    elements::{default_chart::DefaultChart, toggle_button::OptionToggle}, utils::{
+        calculate_velocity::kpc_per_km_s_to_myr,
+        galaxy_model::GalaxyModel,
+        mass_component::total_velocity,
+        radial_grid::{RadialGrid, MAX_RADIUS_LIMITS},
+        translation::{create_text_signal, Translation}
+    }
+};
+
+// Quantity shown by the rotation chart
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RotationView {
+    // Ω = v / r
+    #[default]
+    AngularVelocity,
+    // Time for one orbit, 2πr / v
+    OrbitalPeriod,
+}
+
+impl RotationView {
+    // Value at a radius from the circular velocity there
+    fn value(self, radius: f64, velocity: f64) -> f64 {
+        match self {
+            Self::AngularVelocity => velocity / radius,
+            Self::OrbitalPeriod => 2.0 * PI * radius / velocity * kpc_per_km_s_to_myr(),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+struct RotationPoint {
+    x: f64,
+    y: f64,
+    // Line from the axis to the curve at the solar radius, NaN elsewhere
+    marker: f64,
+}
+
+fn get_rotation_points(model: &GalaxyModel, grid: &RadialGrid, view: RotationView, solar_radius: f64) -> Vec<RotationPoint> {
+    let components = model.components();
+    let value = |x: f64| view.value(x, total_velocity(&components, x));
+
+    let mut points: Vec<RotationPoint> = grid.sample(|x| vec![value(x)])
+        .into_iter()
+        .map(|(x, ys)| RotationPoint { x, y: ys[0], marker: f64::NAN })
+        .collect();
+
+    // Two points at the solar radius draw the marker
+    if (0.0..=grid.max_radius).contains(&solar_radius) {
+        let y = value(solar_radius);
+        let i = points.partition_point(|point| point.x < solar_radius);
+        points.splice(i..i, [
+            RotationPoint { x: solar_radius, y, marker: 0.0 },
+            RotationPoint { x: solar_radius, y, marker: y },
+        ]);
+    }
+
+    points
+}
+
+#[component]
+pub fn RotationChart(
+    text: ReadSignal<Translation>,
+    view: RwSignal<RotationView>,
+    // Radius of the galactic year (kpc)
+    solar_radius: RwSignal<f64>,
+) -> impl IntoView {
+    // Get rotation section of text
+    let rotation_text: Memo<HashMap<String, String>> =
+        Memo::new(move |_| text.get().0.get("rotation").cloned().unwrap_or_default());
+
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let grid = expect_context::<RwSignal<RadialGrid>>();
+
+    let rotation_points = Memo::new(move |_| {
+        let (view, solar_radius) = (view.get(), solar_radius.get());
+        model.with(|model| grid.with(|grid| get_rotation_points(model, grid, view, solar_radius)))
+    });
+
+    let translate = move |key: &'static str| rotation_text.get().get(key).cloned().unwrap_or(key.to_string());
+
+    // Period of one orbit at the solar radius
+    let galactic_year = move || {
+        let radius = solar_radius.get();
+        let period = model.with(|model| {
+            RotationView::OrbitalPeriod.value(radius, total_velocity(&model.components(), radius))
+        });
+        format!("{}: {:.0} Myr", translate("Galactic year"), period)
+    };
+
+    // Invalid values are ignored, the input shows the current radius again
+    let on_solar_radius = move |ev: Event| {
+        let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+        match input.value().parse::<f64>() {
+            Ok(value) if value > 0.0 && value.is_finite() => solar_radius.set(value.min(MAX_RADIUS_LIMITS.1)),
+            _ => input.set_value(&solar_radius.get_untracked().to_string()),
+        }
+    };
+
+    let max_radius = Memo::new(move |_| grid.with(|grid| grid.max_radius));
+
+    move || {
+        let (name, y_label) = match view.get() {
+            RotationView::AngularVelocity => ("Angular velocity", "Angular velocity (km/s/kpc)"),
+            RotationView::OrbitalPeriod => ("Orbital period", "Orbital period (Myr)"),
+        };
+
+        // One line for the curve and one for the marker
+        let series = Series::new(|data: &RotationPoint| data.x)
+            .line(
+                Line::new(|data: &RotationPoint| data.y)
+                    .with_name_dyn(create_text_signal(rotation_text, name.to_string()))
+                    .with_width(3.0),
+            )
+            .line(
+                Line::new(|data: &RotationPoint| data.marker)
+                    .with_name_dyn(create_text_signal(rotation_text, "Solar radius".to_string()))
+                    .with_width(1.0),
+            )
+            .with_min_y(RwSignal::new(Some(0.0)))
+            .with_x_range(0.0, max_radius.get());
+
+        view! {
+            <DefaultChart
+                y_label=y_label.to_string()
+                x_label="Radius (kpc)".to_string()
+                series=series
+                data=rotation_points
+                primary=true
+                label_text=rotation_text
+            >
+                <OptionToggle
+                    selected=view
+                    on_select=move |target| view.set(target)
+                    options=vec![
+                        (RotationView::AngularVelocity, "Angular velocity"),
+                        (RotationView::OrbitalPeriod, "Orbital period"),
+                    ]
+                    text=rotation_text
+                />
+                <label>
+                    {move || translate("Solar radius")}
+                    <input
+                        type="number"
+                        min="0"
+                        step="any"
+                        prop:value=move || solar_radius.get().to_string()
+                        on:change=on_solar_radius
+                    />
+                    "kpc"
+                </label>
+                <span>{galactic_year}</span>
+            </DefaultChart>
+        }
+    }
+}
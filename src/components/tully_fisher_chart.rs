@@ -0,0 +1,126 @@
+use leptos::prelude::*;
+use leptos_chartistry::*;
+use std::collections::HashMap;
+use crate::{
+    elements::default_chart::{apply_y_range, DefaultChart}, utils::{
+        axis_settings::AxisSettings,
+        galaxy_model::GalaxyModel,
+        reference_line::{positive_range, with_reference_line},
+        translation::{create_text_signal, Translation},
+        tully_fisher::{baryonic_mass, flat_velocity, offset, reference_sample, relation_mass}
+    }
+};
+
+// Range of the line beyond the outermost galaxies, as a factor of the velocity
+const LINE_MARGIN: f64 = 1.25;
+
+#[derive(PartialEq, Clone, Debug)]
+struct TullyFisherPoint {
+    x: f64,
+    // Literature sample, relation and model galaxy
+    ys: Vec<f64>
+}
+
+// Flat velocity (km/s) and baryonic mass (10^10 M☉) of the model galaxy
+fn model_galaxy(model: &GalaxyModel) -> (f64, f64) {
+    let components = model.disk_and_halo();
+    let [disk, _] = &components;
+    (
+        flat_velocity(&components, model.disk.scale),
+        baryonic_mass(disk.as_ref(), model.disk.scale),
+    )
+}
+
+// Galaxies and the relation, sorted by the flat velocity
+fn get_tully_fisher_points((model_velocity, model_mass): (f64, f64)) -> Vec<(f64, Vec<f64>)> {
+    let sample = reference_sample();
+    let model_velocity = Some(model_velocity).filter(|v| v.is_finite() && *v > 0.0);
+    let Some((min, max)) = positive_range(sample.iter().map(|galaxy| galaxy.flat_velocity).chain(model_velocity)) else {
+        return Vec::new();
+    };
+    with_reference_line(
+        (min / LINE_MARGIN, max * LINE_MARGIN),
+        |v| vec![f64::NAN, relation_mass(v), f64::NAN],
+        sample
+            .iter()
+            .map(|galaxy| (galaxy.flat_velocity, vec![galaxy.baryonic_mass, relation_mass(galaxy.flat_velocity), f64::NAN]))
+            .chain(model_velocity.map(|v| (v, vec![f64::NAN, relation_mass(v), model_mass]))),
+    )
+}
+
+#[component]
+pub fn TullyFisherChart(
+    text: ReadSignal<Translation>,
+    axes: RwSignal<AxisSettings>,
+) -> impl IntoView {
+    // Get tully_fisher section of text
+    let tully_fisher_text: Memo<HashMap<String, String>> =
+        Memo::new(move |_| text.get().0.get("tully_fisher").cloned().unwrap_or_default());
+
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let galaxy = Memo::new(move |_| model.with(model_galaxy));
+
+    let tully_fisher_points = Memo::new(move |_| {
+        let axes = axes.get();
+
+        // Positions on the (possibly logarithmic) axes, clipped to the limits
+        axes.clipped_positions(get_tully_fisher_points(galaxy.get()))
+            .into_iter()
+            .map(|(x, ys)| TullyFisherPoint { x, ys })
+            .collect::<Vec<_>>()
+    });
+
+    let translate = move |key: &'static str| {
+        move || tully_fisher_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    // Where the model galaxy lies
+    let summary = move || {
+        let (velocity, mass) = galaxy.get();
+        format!(
+            "{}: v = {:.0} km/s, M = {:.2} · 10^10 M☉, {:+.2} dex {}",
+            translate("Model galaxy")(),
+            velocity,
+            mass,
+            offset(mass, velocity),
+            translate("from the relation")(),
+        )
+    };
+
+    move || {
+        let series = Series::new(|data: &TullyFisherPoint| data.x)
+            // Only the markers of the galaxies, without connecting lines
+            .line(
+                Line::new(|data: &TullyFisherPoint| data.ys[0])
+                    .with_name_dyn(create_text_signal(tully_fisher_text, "Literature sample (SPARC)".to_string()))
+                    .with_width(0.0)
+                    .with_marker(RwSignal::new(Marker::from_shape(MarkerShape::Circle).with_scale(1.5))),
+            )
+            .line(
+                Line::new(|data: &TullyFisherPoint| data.ys[1])
+                    .with_name_dyn(create_text_signal(tully_fisher_text, "Baryonic Tully-Fisher relation, fitted to the sample".to_string()))
+                    .with_width(1.5),
+            )
+            .line(
+                Line::new(|data: &TullyFisherPoint| data.ys[2])
+                    .with_name_dyn(create_text_signal(tully_fisher_text, "Model galaxy".to_string()))
+                    .with_width(0.0)
+                    .with_marker(RwSignal::new(Marker::from_shape(MarkerShape::Diamond).with_scale(3.0))),
+            );
+        let series = apply_y_range(series, &axes.get());
+
+        view! {
+            <DefaultChart
+                y_label="Baryonic mass (10^10 * M☉)".to_string()
+                x_label="Flat velocity (km/s)".to_string()
+                series=series
+                data=tully_fisher_points
+                primary=true
+                label_text=tully_fisher_text
+                axes=axes
+            >
+                <span>{summary}</span>
+            </DefaultChart>
+        }
+    }
+}
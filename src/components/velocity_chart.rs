@@ -1,9 +1,18 @@
-use std::f64;
+use std::{collections::BTreeSet, f64};
 
 use crate::{
-    elements::default_chart::DefaultChart,
+    elements::{
+        default_chart::DefaultChart,
+        toggle_button::{OptionToggle, ToggleButton},
+    },
     utils::{
-        calculate_velocity::calculate_velocity, intersection::x_intersection,
+        curve_features::{curve_features, CurveFeatures},
+        galaxy_model::GalaxyModel,
+        mass_component::total_velocity,
+        observed::{alignment_radii, ngc_3198, observed_at, Alignment, ObservedPoint},
+        clipping::{clip, Bounds},
+        evaluation_radius::EvaluationRadius,
+        radial_grid::RadialGrid,
         translation::{create_text_signal, Translation},
     },
 };
@@ -13,190 +22,305 @@ use leptos_chartistry::*;
 // Vertical limit of chart
 const CHART_BOUND: f64 = 300.0;
 
-#[derive(PartialEq)]
+// Legend names of the observed and the total curve
+const SAMPLES_NAME: &str = "Sample Values (NGC 3198)";
+const TOTAL_NAME: &str = "Galaxy";
+// Toggle of the markers of the curve features
+const FEATURES_NAME: &str = "Curve features";
+
+// Vertical markers: the evaluation radius, then the features of the curve
+const MARKER_NAMES: [&str; 4] = [
+    "Evaluation radius",
+    "Peak velocity",
+    "Disk and halo velocity equal",
+    "Halo holds half the mass",
+];
+
+#[derive(PartialEq, Clone)]
 struct CombinedPoints {
     x: f64,
     y: f64,
     y2: f64,
+    // Vertical lines of the markers, NaN elsewhere
+    markers: Vec<f64>,
+    // Flat velocity, drawn across the chart
+    flat_velocity: f64,
+    // Velocity of each component
+    components: Vec<f64>,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 struct VelocityPoint {
     x: f64,
     y: f64,
+    components: Vec<f64>,
 }
 
-impl VelocityPoint {
-    fn new(x: f64, y: f64) -> Self {
-        Self { x, y }
-    }
-}
+// Model velocities at the sampled radii and at the given extra radii inside the grid
+fn get_velocity_points(model: &GalaxyModel, grid: &RadialGrid, extra_radii: &[f64]) -> Vec<VelocityPoint> {
+    let components = model.components();
 
-fn get_defined_points() -> Vec<VelocityPoint> {
-    vec![
-        VelocityPoint::new(0.0, 0.0),
-        VelocityPoint::new(1.0, 55.0),
-        VelocityPoint::new(2.0, 92.0),
-        VelocityPoint::new(3.0, 110.0),
-        VelocityPoint::new(4.0, 123.0),
-        VelocityPoint::new(5.0, 134.0),
-        VelocityPoint::new(6.0, 142.0),
-        VelocityPoint::new(7.0, 145.0),
-        VelocityPoint::new(8.0, 147.0),
-        VelocityPoint::new(9.0, 148.0),
-        VelocityPoint::new(10.0, 152.0),
-        VelocityPoint::new(11.0, 155.0),
-        VelocityPoint::new(12.0, 156.0),
-        VelocityPoint::new(13.0, 157.0),
-        VelocityPoint::new(14.0, 153.0),
-        VelocityPoint::new(15.0, 154.0),
-        VelocityPoint::new(16.0, 153.0),
-        VelocityPoint::new(17.0, 150.0),
-        VelocityPoint::new(18.0, 149.0),
-        VelocityPoint::new(19.0, 148.0),
-        VelocityPoint::new(20.0, 146.0),
-        VelocityPoint::new(21.0, 147.0),
-        VelocityPoint::new(22.0, 148.0),
-        VelocityPoint::new(23.0, 148.0),
-        VelocityPoint::new(24.0, 149.0),
-        VelocityPoint::new(25.0, 150.0),
-        VelocityPoint::new(26.0, 150.0),
-        VelocityPoint::new(27.0, 149.0),
-    ]
-}
+    // The total is computed as the last curve
+    let velocities = |x: f64| {
+        let mut ys: Vec<f64> = components.iter().map(|component| component.velocity(x)).collect();
+        // Velocities add in quadrature
+        ys.push(ys.iter().map(|y| y.powi(2)).sum::<f64>().sqrt());
+        ys
+    };
 
-fn get_velocity_points(
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
-    iso_nfw: ReadSignal<bool>,
-) -> Vec<VelocityPoint> {
-    // Retrieve properties from signal
-    let properties = slider_values.get();
-    let iso_nfw_resolved = iso_nfw.get();
+    let mut points = grid.sample(velocities);
+    points.extend(
+        extra_radii
+            .iter()
+            .filter(|&&x| (0.0..=grid.max_radius).contains(&x))
+            .map(|&x| (x, velocities(x))),
+    );
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    points.dedup_by(|a, b| a.0 == b.0);
 
-    let mut velocity_points = Vec::new();
-
-    // Compute points
-    for i in (0..182).map(|x| x as f64 * 0.25) {
-        let x: f64 = i as f64;
-        let y = calculate_velocity(
+    points
+        .into_iter()
+        .map(|(x, mut ys)| VelocityPoint {
             x,
-            properties.0,
-            properties.1,
-            properties.2,
-            properties.3,
-            iso_nfw_resolved,
-        );
-        velocity_points.push(VelocityPoint::new(x, y));
-    }
-
-    velocity_points
+            y: ys.pop().unwrap_or(f64::NAN),
+            components: ys,
+        })
+        .collect()
 }
 
-// Check for an intersection at CHART_BOUND; If exists: compute point of intersection.
-fn check_intersection(
-    i: usize,
-    velocity_points: &[VelocityPoint],
-    defined_points: &[VelocityPoint],
-    velocity: &VelocityPoint,
-    combined: &mut Vec<CombinedPoints>,
-) {
-    // If != first point and y > CHART_BOUND + previous point < CHART_BOUND
-    if i > 0 && velocity.y > CHART_BOUND {
-        let prev = velocity_points[i - 1];
-
-        if prev.y < CHART_BOUND {
-            let intersect_x = x_intersection(prev.x, prev.y, velocity.x, velocity.y, CHART_BOUND);
-            let defined_y = defined_points.get((i - 1) / 2).map_or(f64::NAN, |dp| dp.y);
-
-            let intersection_point = CombinedPoints {
-                x: intersect_x,
-                y: CHART_BOUND,
-                y2: defined_y,
-            };
-            combined.push(intersection_point);
-        }
-    }
-}
-
-// Combines defined and calculated points
+// Combines defined and calculated points, with the calculated curves clipped to the bounds
 fn combine_points(
     velocity_points: &[VelocityPoint],
-    defined_points: &[VelocityPoint],
+    defined_points: &[ObservedPoint],
+    alignment: Alignment,
+    bounds: &Bounds,
 ) -> Vec<CombinedPoints> {
-    let mut combined: Vec<CombinedPoints> = Vec::new();
-
-    for (i, velocity) in velocity_points.iter().enumerate() {
-        check_intersection(i, velocity_points, defined_points, velocity, &mut combined);
-
-        // Check if fits into CHART_BOUND, otherwise use NaN
-        let velocity_y = if velocity.y > CHART_BOUND {
-            f64::NAN
-        } else {
-            velocity.y
-        };
-
-        // Get defined point if exists, otherwise use NaN
-        let defined_y = defined_points.get(i / 2).map_or(f64::NAN, |dp| dp.y);
+    // The total is clipped as the last curve
+    let curves: Vec<(f64, Vec<f64>)> = velocity_points
+        .iter()
+        .map(|velocity| {
+            let mut ys = velocity.components.clone();
+            ys.push(velocity.y);
+            (velocity.x, ys)
+        })
+        .collect();
 
-        // Combine and push point
-        let current_point = CombinedPoints {
-            x: velocity.x,
-            y: velocity_y,
-            y2: defined_y,
-        };
+    clip(&curves, bounds)
+        .into_iter()
+        .map(|(x, mut ys)| CombinedPoints {
+            x,
+            y: ys.pop().unwrap_or(f64::NAN),
+            // Observed value at the same radius, NaN outside of the observed radii
+            y2: observed_at(defined_points, x, alignment),
+            markers: vec![f64::NAN; MARKER_NAMES.len()],
+            flat_velocity: f64::NAN,
+            components: ys,
+        })
+        .collect()
+}
 
-        combined.push(current_point);
+// Draw a marker as a vertical line from two copies of the point at its radius
+fn insert_marker(points: &mut Vec<CombinedPoints>, marker: usize, radius: f64, top: f64) {
+    if let Some(i) = points.iter().position(|point| point.x == radius) {
+        let mut top_point = points[i].clone();
+        top_point.markers[marker] = top.min(CHART_BOUND);
+        points[i].markers[marker] = 0.0;
+        points.insert(i + 1, top_point);
     }
+}
 
-    combined
+// Radii of the feature markers with the velocity they reach up to
+fn feature_markers(features: &CurveFeatures, model: &GalaxyModel) -> Vec<(usize, f64, f64)> {
+    let components = model.disk_and_halo();
+    let [disk, _] = &components;
+    let total = |radius: f64| total_velocity(&components, radius);
+    [
+        features.peak.map(|(radius, velocity)| (1, radius, velocity)),
+        features.crossing_radius.map(|radius| (2, radius, disk.velocity(radius))),
+        features.half_mass_radius.map(|radius| (3, radius, total(radius))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
 }
 
 #[component]
 pub fn VelocityChart(
-    slider_values: ReadSignal<(f64, f64, f64, f64)>,
-    iso_nfw: ReadSignal<bool>,
     text: ReadSignal<Translation>,
+    alignment: RwSignal<Alignment>,
+    // Names of the curves switched off in the legend
+    hidden: RwSignal<BTreeSet<String>>,
 ) -> impl IntoView {
     // Get velocity section of text
     let velocity_text: Memo<std::collections::HashMap<String, String>> =
         Memo::new(move |_| text.get().0.get("velocity").cloned().unwrap_or_default());
-    
+
+    let model = expect_context::<RwSignal<GalaxyModel>>();
+    let grid = expect_context::<RwSignal<RadialGrid>>();
+    let evaluation_radius = expect_context::<RwSignal<EvaluationRadius>>();
+
+    let features = Memo::new(move |_| {
+        let max_radius = grid.with(|grid| grid.max_radius);
+        model.with(|model| curve_features(model, max_radius))
+    });
+
     // Memo of the final data
     let combined_points = Memo::new(move |_| {
-        let defined_points = get_defined_points();
-        let velocity_points = get_velocity_points(slider_values, iso_nfw);
+        let defined_points = ngc_3198();
+        let alignment = alignment.get();
+        let marker_radius = model.with(|model| evaluation_radius.get().resolve(model));
+        let features = features.get();
+        let feature_markers = model.with(|model| feature_markers(&features, model));
+        // The observed radii are sampled as well, so the observed curve is drawn at its own radii
+        let mut extra_radii = alignment_radii(&defined_points, alignment);
+        extra_radii.extend(marker_radius);
+        extra_radii.extend(feature_markers.iter().map(|&(_, radius, _)| radius));
+        let (velocity_points, max_radius) = grid.with(|grid| {
+            (model.with(|model| get_velocity_points(model, grid, &extra_radii)), grid.max_radius)
+        });
+        let bounds = Bounds::y(Some(0.0), Some(CHART_BOUND)).with_x(0.0, max_radius);
 
-        combine_points(&velocity_points, &defined_points)
+        let mut combined = combine_points(&velocity_points, &defined_points, alignment, &bounds);
+        // Drawn only inside the chart
+        let flat_velocity = Some(features.flat_velocity)
+            .filter(|velocity| (0.0..=CHART_BOUND).contains(velocity))
+            .unwrap_or(f64::NAN);
+        for point in combined.iter_mut() {
+            point.flat_velocity = flat_velocity;
+        }
+        if let Some(radius) = marker_radius {
+            insert_marker(&mut combined, 0, radius, CHART_BOUND);
+        }
+        for (marker, radius, top) in feature_markers {
+            insert_marker(&mut combined, marker, radius, top);
+        }
+        combined
     });
-    
-
-    // Two lines, one for computed, one for pre-defined points
-    let series = Series::new(|data: &CombinedPoints| data.x)
-        .line(
-            Line::new(|data: &CombinedPoints| data.y2)
-                .with_name_dyn(
-                    create_text_signal(velocity_text, "Sample Values (NGC 3198)".to_string())
-                )
-                .with_interpolation(Step::Horizontal),
-        )
-        .line(
-            Line::new(|data: &CombinedPoints| data.y)
-                .with_name_dyn(
-                    create_text_signal(velocity_text, "Galaxy".to_string())
-                )
-                .with_width(3.0),
-        )
-        .with_y_range(0.0, CHART_BOUND)
-        .with_x_range(0.0, 45.0);
-
-    view! {
-        <DefaultChart
-            y_label="Velocity (km/s)".to_string()
-            x_label="Radius (kpc)".to_string()
-            series=series
-            data=combined_points
-            primary=true
-            label_text=velocity_text
-        />
+
+    // Names of the components, the chart is only rebuilt if they change
+    let component_names = Memo::new(move |_| {
+        model.with(|model| {
+            model
+                .components()
+                .iter()
+                .map(|component| component.name())
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let max_radius = Memo::new(move |_| grid.with(|grid| grid.max_radius));
+
+    // Clicking on the chart sets the radius of the mass details
+    let on_click = Callback::new(move |fraction: f64| {
+        evaluation_radius.set(EvaluationRadius::Fixed(fraction * max_radius.get_untracked()));
+    });
+
+    let translate = move |key: &'static str| {
+        move || velocity_text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    // Button switching a curve on and off, highlighted while it is shown
+    let series_toggle = move |name: &'static str| {
+        view! {
+            <ToggleButton
+                active=Signal::derive(move || hidden.with(|hidden| !hidden.contains(name)))
+                on_click=move || {
+                    hidden.update(|hidden| {
+                        if !hidden.remove(name) {
+                            hidden.insert(name.to_string());
+                        }
+                    })
+                }
+            >
+                {translate(name)}
+            </ToggleButton>
+        }
+    };
+
+    move || {
+        // Hidden curves are drawn without values, so they keep their color and legend entry
+        let hidden_curves = hidden.get();
+        let shown = move |name: &str| !hidden_curves.contains(name);
+
+        // Observed values change halfway between their radii with the nearest sample
+        let interpolation = match alignment.get() {
+            Alignment::Interpolated => Interpolation::Linear,
+            Alignment::Nearest => Interpolation::Step(Step::Horizontal),
+        };
+
+        // One line for the pre-defined points, one for the total and one for each component
+        let (samples_shown, total_shown) = (shown(SAMPLES_NAME), shown(TOTAL_NAME));
+        let mut series = Series::new(|data: &CombinedPoints| data.x)
+            .line(
+                Line::new(move |data: &CombinedPoints| if samples_shown { data.y2 } else { f64::NAN })
+                    .with_name_dyn(
+                        create_text_signal(velocity_text, SAMPLES_NAME.to_string())
+                    )
+                    .with_interpolation(RwSignal::new(interpolation)),
+            )
+            .line(
+                Line::new(move |data: &CombinedPoints| if total_shown { data.y } else { f64::NAN })
+                    .with_name_dyn(
+                        create_text_signal(velocity_text, TOTAL_NAME.to_string())
+                    )
+                    .with_width(3.0),
+            );
+
+        for (i, name) in component_names.get().into_iter().enumerate() {
+            let component_shown = shown(name);
+            series = series.line(
+                Line::new(move |data: &CombinedPoints| match data.components.get(i) {
+                    Some(&velocity) if component_shown => velocity,
+                    _ => f64::NAN,
+                })
+                    .with_name_dyn(create_text_signal(velocity_text, name.to_string()))
+                    .with_width(1.5),
+            );
+        }
+
+        // The feature markers are switched together
+        let features_shown = shown(FEATURES_NAME);
+        for (i, name) in MARKER_NAMES.into_iter().enumerate() {
+            series = series.line(
+                Line::new(move |data: &CombinedPoints| if i == 0 || features_shown { data.markers[i] } else { f64::NAN })
+                    .with_name_dyn(create_text_signal(velocity_text, name.to_string()))
+                    .with_width(1.0),
+            );
+        }
+
+        let series = series
+            .line(
+                Line::new(move |data: &CombinedPoints| if features_shown { data.flat_velocity } else { f64::NAN })
+                    .with_name_dyn(create_text_signal(velocity_text, "Flat velocity".to_string()))
+                    .with_width(1.0),
+            )
+            .with_y_range(0.0, CHART_BOUND)
+            .with_x_range(0.0, max_radius.get());
+
+        view! {
+            <DefaultChart
+                y_label="Velocity (km/s)".to_string()
+                x_label="Radius (kpc)".to_string()
+                series=series
+                data=combined_points
+                primary=true
+                label_text=velocity_text
+                on_click=on_click
+            >
+                {series_toggle(SAMPLES_NAME)}
+                {series_toggle(TOTAL_NAME)}
+                {component_names.get().into_iter().map(series_toggle).collect_view()}
+                {series_toggle(FEATURES_NAME)}
+                <OptionToggle
+                    selected=alignment
+                    on_select=move |target| alignment.set(target)
+                    options=vec![
+                        (Alignment::Interpolated, "Interpolated samples"),
+                        (Alignment::Nearest, "Nearest sample"),
+                    ]
+                    text=velocity_text
+                />
+            </DefaultChart>
+        }
     }
 }
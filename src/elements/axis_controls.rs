@@ -0,0 +1,70 @@
+use crate::{
+    elements::toggle_button::ToggleButton,
+    utils::axis_settings::{AxisScale, AxisSettings},
+};
+use leptos::prelude::*;
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlInputElement};
+
+#[component]
+pub fn AxisControls(
+    axes: RwSignal<AxisSettings>,
+    text: Memo<HashMap<String, String>>,
+) -> impl IntoView {
+    let translate = move |key: &'static str| {
+        move || text.get().get(key).cloned().unwrap_or(key.to_string())
+    };
+
+    // Empty input for an automatic limit; invalid values are ignored
+    let limit_handle = move |set_limit: fn(&mut AxisSettings, Option<f64>)| {
+        move |ev: Event| {
+            let input = ev.target().unwrap().dyn_into::<HtmlInputElement>().unwrap();
+            let value = input.value();
+            if value.trim().is_empty() {
+                axes.update(|axes| set_limit(axes, None));
+            } else if let Ok(limit) = value.trim().parse::<f64>() {
+                axes.update(|axes| set_limit(axes, Some(limit)));
+            }
+        }
+    };
+
+    let format_limit = |limit: Option<f64>| limit.map(|limit| limit.to_string()).unwrap_or_default();
+
+    view! {
+        <div class="axis-controls">
+            <ToggleButton
+                active=Signal::derive(move || axes.with(|axes| axes.x_scale == AxisScale::Logarithmic))
+                on_click=move || axes.update(|axes| axes.x_scale = axes.x_scale.toggle())
+            >
+                {translate("Logarithmic x axis")}
+            </ToggleButton>
+            <ToggleButton
+                active=Signal::derive(move || axes.with(|axes| axes.y_scale == AxisScale::Logarithmic))
+                on_click=move || axes.update(|axes| axes.y_scale = axes.y_scale.toggle())
+            >
+                {translate("Logarithmic y axis")}
+            </ToggleButton>
+            <label>
+                {translate("Minimum")}
+                <input
+                    type="number"
+                    step="any"
+                    placeholder=translate("auto")
+                    prop:value=move || axes.with(|axes| format_limit(axes.y_min))
+                    on:change=limit_handle(|axes, limit| axes.y_min = limit)
+                />
+            </label>
+            <label>
+                {translate("Maximum")}
+                <input
+                    type="number"
+                    step="any"
+                    placeholder=translate("auto")
+                    prop:value=move || axes.with(|axes| format_limit(axes.y_max))
+                    on:change=limit_handle(|axes, limit| axes.y_max = limit)
+                />
+            </label>
+        </div>
+    }
+}
@@ -1,7 +1,50 @@
 use leptos::prelude::*;
 
 use leptos_chartistry::*;
-use crate::utils::translation::create_text_signal;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, MouseEvent};
+use crate::{
+    elements::axis_controls::AxisControls,
+    utils::{
+        axis_settings::{AxisScale, AxisSettings},
+        chart_layout::{plot_position, y_tick_label, FONT_HEIGHT, FONT_WIDTH, PADDING, Y_TICK_CHARS},
+        translation::create_text_signal,
+    },
+};
+
+// Tick labels of an axis; log axes are labeled with the values instead of their logarithm
+fn tick_labels(scale: AxisScale) -> TickLabels<f64> {
+    match scale {
+        AxisScale::Linear => TickLabels::aligned_floats(),
+        AxisScale::Logarithmic => TickLabels::aligned_floats()
+            .with_format(|value, _| AxisScale::Logarithmic.format(value.position(), 2)),
+    }
+}
+
+// Applies the limits of the y axis, the remaining range is automatic
+pub fn apply_y_range<T: 'static>(series: Series<T, f64, f64>, axes: &AxisSettings) -> Series<T, f64, f64> {
+    let (y_min, y_max) = axes.y_bounds();
+    series
+        .with_min_y(RwSignal::new(y_min))
+        .with_max_y(RwSignal::new(y_max))
+}
+
+// Range of a chart over the radius; a linear x axis starts at the center
+pub fn apply_range<T: 'static>(series: Series<T, f64, f64>, axes: &AxisSettings, max_radius: f64) -> Series<T, f64, f64> {
+    let series = apply_y_range(series, axes);
+    if axes.x_scale == AxisScale::Linear {
+        series.with_x_range(0.0, max_radius)
+    } else {
+        series
+    }
+}
+
+// Position of a click along the x axis, from 0 at the left to 1 at the right edge of the plot;
+// None outside of the plot
+fn click_position(ev: &MouseEvent) -> Option<f64> {
+    let chart = ev.current_target()?.dyn_into::<Element>().ok()?.get_bounding_client_rect();
+    plot_position(ev.client_x() as f64 - chart.left(), chart.width())
+}
 
 #[component]
 pub fn DefaultChart<T: 'static + Send + Sync>(
@@ -11,17 +54,31 @@ pub fn DefaultChart<T: 'static + Send + Sync>(
     data: Memo<Vec<T>>,
     primary: bool,
     label_text: Memo<std::collections::HashMap<String, String>>,
+    // Scales and limits of the axes, shows the controls if given
+    #[prop(optional)]
+    axes: Option<RwSignal<AxisSettings>>,
+    // Further controls below the chart
+    #[prop(optional)]
+    children: Option<Children>,
+    // Called with the position of clicks on the plot, see `click_position`
+    #[prop(optional)]
+    on_click: Option<Callback<f64>>,
 ) -> impl IntoView {
     log::info!("test");
+    // The chart is rebuilt by its parent if the scales change
+    let (x_scale, y_scale) = axes
+        .map(|axes| axes.with_untracked(|axes| (axes.x_scale, axes.y_scale)))
+        .unwrap_or_default();
+
     // Chart tooltip
     let tooltip = Tooltip::new(
         TooltipPlacement::RightCursor,
-        TickLabels::aligned_floats().with_format(|value, _| format!("{:.1}", value)),
+        TickLabels::aligned_floats().with_format(move |value, _| x_scale.format(value.position(), 1)),
         TickLabels::aligned_floats().with_format(move |value, _| {
             if value.position().is_nan() {
                 "-".to_string()
             } else {
-                format!("{:.2}", value.position())
+                y_scale.format(value.position(), 2)
             }
         }),
     )
@@ -30,31 +87,49 @@ pub fn DefaultChart<T: 'static + Send + Sync>(
 
     view! {
         <div class=format!("chart {}", {if primary{"chart_primary"} else {""}})>
-            <Chart
-                aspect_ratio=AspectRatio::from_env()
-                series=series
-                data=data
-                left=vec![
-                    // Use dynamic labeling
-                    RotatedLabel::new_dyn(Anchor::End, create_text_signal(label_text, y_label)).into(),
-                    TickLabels::aligned_floats().into(),
-                ]
-                bottom=vec![
-                    TickLabels::aligned_floats().into(),
-                    // Use dynamic labeling
-                    RotatedLabel::new_dyn(Anchor::End, create_text_signal(label_text, x_label)).into(),
-                    Legend::middle().into(),
-                ]
-                inner=[
-                    AxisMarker::left_edge().into_inner(),
-                    AxisMarker::bottom_edge().into_inner(),
-                    XGridLine::default().into_inner(),
-                    YGridLine::default().into_inner(),
-                    YGuideLine::over_mouse().into_inner(),
-                    XGuideLine::over_data().into_inner(),
-                ]
-                tooltip=tooltip
-            />
+            // Clicks are taken on the chart only, so its box is the one of the plot and its labels
+            <div on:click=move |ev| {
+                if let Some(on_click) = on_click {
+                    if let Some(position) = click_position(&ev) {
+                        on_click.run(position);
+                    }
+                }
+            }>
+                <Chart
+                    aspect_ratio=AspectRatio::from_env()
+                    font_height=FONT_HEIGHT
+                    font_width=FONT_WIDTH
+                    padding=Padding::from(PADDING)
+                    series=series
+                    data=data
+                    left=vec![
+                        // Use dynamic labeling
+                        RotatedLabel::new_dyn(Anchor::End, create_text_signal(label_text, y_label)).into(),
+                        // Fixed width, so the plot always starts at the same distance from the left edge
+                        TickLabels::aligned_floats()
+                            .with_format(move |value, _| y_tick_label(y_scale, value.position()))
+                            .with_min_chars(Y_TICK_CHARS)
+                            .into(),
+                    ]
+                    bottom=vec![
+                        tick_labels(x_scale).into(),
+                        // Use dynamic labeling
+                        RotatedLabel::new_dyn(Anchor::End, create_text_signal(label_text, x_label)).into(),
+                        Legend::middle().into(),
+                    ]
+                    inner=[
+                        AxisMarker::left_edge().into_inner(),
+                        AxisMarker::bottom_edge().into_inner(),
+                        XGridLine::default().into_inner(),
+                        YGridLine::default().into_inner(),
+                        YGuideLine::over_mouse().into_inner(),
+                        XGuideLine::over_data().into_inner(),
+                    ]
+                    tooltip=tooltip
+                />
+            </div>
+            {axes.map(|axes| view! { <AxisControls axes=axes text=label_text /> })}
+            {children.map(|children| view! { <div class="axis-controls">{children()}</div> })}
         </div>
     }
 }
@@ -1,2 +1,4 @@
 pub mod default_chart;
-pub mod tex_equation;
\ No newline at end of file
+pub mod tex_equation;
+pub mod axis_controls;
+pub mod toggle_button;
\ No newline at end of file
@@ -0,0 +1,45 @@
+use leptos::prelude::*;
+use std::collections::HashMap;
+
+// Button switching something on or off, highlighted while it is on
+#[component]
+pub fn ToggleButton(
+    #[prop(into)] active: Signal<bool>,
+    #[prop(into)] on_click: Callback<()>,
+    children: Children,
+) -> impl IntoView {
+    view! {
+        <button
+            class=move || if active.get() { "axis-toggle axis-toggle-active" } else { "axis-toggle" }
+            on:click=move |_| on_click.run(())
+        >
+            {children()}
+        </button>
+    }
+}
+
+// One button for each option, labelled with its translated key, the selected one highlighted
+#[component]
+pub fn OptionToggle<T>(
+    #[prop(into)] selected: Signal<T>,
+    on_select: impl Fn(T) + Copy + Send + Sync + 'static,
+    options: Vec<(T, &'static str)>,
+    text: Memo<HashMap<String, String>>,
+) -> impl IntoView
+where
+    T: Copy + PartialEq + Send + Sync + 'static,
+{
+    options
+        .into_iter()
+        .map(|(target, key)| {
+            view! {
+                <ToggleButton
+                    active=Signal::derive(move || selected.get() == target)
+                    on_click=move || on_select(target)
+                >
+                    {move || text.get().get(key).cloned().unwrap_or(key.to_string())}
+                </ToggleButton>
+            }
+        })
+        .collect_view()
+}
@@ -5,7 +5,7 @@ use leptos_router::{components::*, path};
 // Modules
 mod components;
 mod pages;
-mod utils;
+pub mod utils;
 mod elements;
 
 // Top-Level pages
@@ -1,9 +1,22 @@
 use crate::components::{
-    density_chart::DensityChart, inputs::Inputs, mass_chart::MassChart, misc::Misc,
+    density_chart::DensityChart, galaxy_animation::GalaxyAnimation, inputs::Inputs,
+    inversion_chart::{InversionChart, InversionView},
+    mass_chart::{MassChart, MassView}, misc::Misc, orbit_chart::{OrbitChart, OrbitView},
+    potential_chart::{PotentialChart, PotentialView}, rar_chart::RarChart,
+    rotation_chart::{RotationChart, RotationView}, tully_fisher_chart::TullyFisherChart,
     velocity_chart::VelocityChart,
 };
-use crate::utils::translation::{get_translation, Translation};
+use crate::utils::{
+    axis_settings::{AxisScale, AxisSettings},
+    evaluation_radius::EvaluationRadius,
+    galaxy_model::GalaxyModel,
+    observed::Alignment,
+    orbit::OrbitSettings,
+    radial_grid::RadialGrid,
+    translation::{get_translation, Translation},
+};
 use leptos::{ev::resize, prelude::*};
+use std::collections::BTreeSet;
 use icondata as i;
 use leptos_icons::Icon;
 use wasm_bindgen_futures::spawn_local;
@@ -49,9 +62,36 @@ pub fn update_text(language: ReadSignal<String>, set_text: impl Fn(Translation)
 #[component]
 pub fn Home() -> impl IntoView {
     let (mode, set_mode) = signal(String::from("velocity"));
-    // dens_disk, scale_disk, dens_halo, scale_halo
-    let (slider_values, set_slider_values) = signal((1.01, 4.5, 1.52e-21, 15.91));
-    let (iso_nfw, set_iso_nfw) = signal(true);
+    // Parameters of the galaxy, shared with all inputs and charts
+    let model = RwSignal::new(GalaxyModel::default());
+    provide_context(model);
+    // Radii at which all charts are sampled
+    provide_context(RwSignal::new(RadialGrid::default()));
+    // Radius of the mass details, also marked in the velocity chart
+    provide_context(RwSignal::new(EvaluationRadius::default()));
+
+    // Axes of the mass chart, linear by default
+    let mass_axes = RwSignal::new(AxisSettings::default());
+    let mass_view = RwSignal::new(MassView::default());
+    let potential_view = RwSignal::new(PotentialView::default());
+    let rotation_view = RwSignal::new(RotationView::default());
+    // Radius of the galactic year, the distance of the sun from the center of the Milky Way (kpc)
+    let solar_radius = RwSignal::new(8.2);
+    let animation_disk_only = RwSignal::new(false);
+    let orbit_view = RwSignal::new(OrbitView::default());
+    let orbit_settings = RwSignal::new(OrbitSettings::default());
+    // Axes of the density chart; densities span several decades, so the y axis is log by default
+    let density_axes = RwSignal::new(AxisSettings::new(AxisScale::Linear, AxisScale::Logarithmic));
+    let inversion_view = RwSignal::new(InversionView::default());
+    let inversion_mass_axes = RwSignal::new(AxisSettings::default());
+    let inversion_density_axes = RwSignal::new(AxisSettings::new(AxisScale::Linear, AxisScale::Logarithmic));
+    // Accelerations span several decades
+    let rar_axes = RwSignal::new(AxisSettings::new(AxisScale::Logarithmic, AxisScale::Logarithmic));
+    let tully_fisher_axes = RwSignal::new(AxisSettings::new(AxisScale::Logarithmic, AxisScale::Logarithmic));
+    // How the observed curve is placed between its radii
+    let sample_alignment = RwSignal::new(Alignment::default());
+    // Curves switched off in the velocity chart
+    let hidden_velocities = RwSignal::new(BTreeSet::new());
 
     let orientation: ReadSignal<bool> = get_orientation();
 
@@ -162,13 +202,39 @@ pub fn Home() -> impl IntoView {
                 }}
             </h1>
             <Show when=move || { mode.get() == "velocity" }>
-                <VelocityChart slider_values=slider_values iso_nfw=iso_nfw text=text />
+                <VelocityChart text=text alignment=sample_alignment hidden=hidden_velocities />
             </Show>
             <Show when=move || { mode.get() == "mass" }>
-                <MassChart slider_values=slider_values iso_nfw=iso_nfw text=text />
+                <MassChart text=text axes=mass_axes view=mass_view />
             </Show>
             <Show when=move || { mode.get() == "density" }>
-                <DensityChart slider_values=slider_values iso_nfw=iso_nfw text=text />
+                <DensityChart text=text axes=density_axes />
+            </Show>
+            <Show when=move || { mode.get() == "potential" }>
+                <PotentialChart text=text view=potential_view />
+            </Show>
+            <Show when=move || { mode.get() == "rotation" }>
+                <RotationChart text=text view=rotation_view solar_radius=solar_radius />
+            </Show>
+            <Show when=move || { mode.get() == "animation" }>
+                <GalaxyAnimation text=text disk_only=animation_disk_only />
+            </Show>
+            <Show when=move || { mode.get() == "orbit" }>
+                <OrbitChart text=text view=orbit_view settings=orbit_settings />
+            </Show>
+            <Show when=move || { mode.get() == "inversion" }>
+                <InversionChart
+                    text=text
+                    view=inversion_view
+                    mass_axes=inversion_mass_axes
+                    density_axes=inversion_density_axes
+                />
+            </Show>
+            <Show when=move || { mode.get() == "rar" }>
+                <RarChart text=text axes=rar_axes />
+            </Show>
+            <Show when=move || { mode.get() == "tully_fisher" }>
+                <TullyFisherChart text=text axes=tully_fisher_axes />
             </Show>
             <Show
                 when=move || orientation.get()
@@ -210,40 +276,19 @@ pub fn Home() -> impl IntoView {
                                     when=move || home_tab_mode.get()
                                     fallback=move || {
                                         view! {
-                                            <Misc
-                                                mode=mode
-                                                iso_nfw=iso_nfw
-                                                slider_values=slider_values
-                                                text=text
-                                            />
+                                            <Misc mode=mode text=text />
                                         }
                                     }
                                 >
-                                    <Inputs
-                                        set_mode=set_mode
-                                        mode=mode
-                                        slider_values=slider_values
-                                        set_slider_values=set_slider_values
-                                        iso_nfw=iso_nfw
-                                        set_iso_nfw=set_iso_nfw
-                                        text=text
-                                    />
+                                    <Inputs set_mode=set_mode mode=mode text=text />
                                 </Show>
                             </div>
                         </div>
                     }
                 }
             >
-                <Inputs
-                    set_mode=set_mode
-                    mode=mode
-                    slider_values=slider_values
-                    set_slider_values=set_slider_values
-                    iso_nfw=iso_nfw
-                    set_iso_nfw=set_iso_nfw
-                    text=text
-                />
-                <Misc mode=mode iso_nfw=iso_nfw slider_values=slider_values text=text />
+                <Inputs set_mode=set_mode mode=mode text=text />
+                <Misc mode=mode text=text />
             </Show>
 	    <div id="privacy">This "is a static website that does not collect nor store any data."</div>
         </ErrorBoundary>
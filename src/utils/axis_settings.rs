@@ -0,0 +1,113 @@
+// Scales and limits of chart axes.
+// Logarithmic axes are drawn by plotting log10 of the values and labeling the ticks with the values.
+
+use crate::utils::clipping::{clip, Bounds};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AxisScale {
+    #[default]
+    Linear,
+    Logarithmic,
+}
+
+impl AxisScale {
+    // Position of a value on the axis; values which can not be shown (e.g. the infinite
+    // density of a cusp, or zero on a log axis) become NaN, so the automatic range ignores them
+    pub fn transform(self, value: f64) -> f64 {
+        match self {
+            _ if !value.is_finite() => f64::NAN,
+            Self::Linear => value,
+            Self::Logarithmic if value > 0.0 => value.log10(),
+            Self::Logarithmic => f64::NAN,
+        }
+    }
+
+    // Value at a position on the axis
+    pub fn inverse(self, position: f64) -> f64 {
+        match self {
+            Self::Linear => position,
+            Self::Logarithmic => 10.0_f64.powf(position),
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Linear => Self::Logarithmic,
+            Self::Logarithmic => Self::Linear,
+        }
+    }
+
+    // Label of a position on the axis
+    pub fn format(self, position: f64, precision: usize) -> String {
+        match self {
+            Self::Linear => format!("{:.*}", precision, position),
+            Self::Logarithmic => {
+                let value = self.inverse(position);
+                if (1.0e-2..1.0e4).contains(&value) {
+                    // Significant digits only
+                    let decimals = (precision as i32 - value.log10().floor() as i32).max(0) as usize;
+                    let label = format!("{:.*}", decimals, value);
+                    if label.contains('.') {
+                        label.trim_end_matches('0').trim_end_matches('.').to_string()
+                    } else {
+                        label
+                    }
+                } else {
+                    format!("{:.*e}", precision.saturating_sub(1), value)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AxisSettings {
+    pub x_scale: AxisScale,
+    pub y_scale: AxisScale,
+    // Limits of the y axis in the units of the values, None for automatic
+    pub y_min: Option<f64>,
+    pub y_max: Option<f64>,
+}
+
+impl AxisSettings {
+    pub fn new(x_scale: AxisScale, y_scale: AxisScale) -> Self {
+        Self {
+            x_scale,
+            y_scale,
+            y_min: None,
+            y_max: None,
+        }
+    }
+
+    // Limits of the y axis as positions on the axis, None if automatic or not representable
+    pub fn y_bounds(&self) -> (Option<f64>, Option<f64>) {
+        let position = |limit: Option<f64>| {
+            limit
+                .map(|limit| self.y_scale.transform(limit))
+                .filter(|position| position.is_finite())
+        };
+        (position(self.y_min), position(self.y_max))
+    }
+
+    // Position of a point on the chart, None if its x can not be shown (e.g. the center on a log axis),
+    // since the chart orders and ranges the points by x
+    pub fn position(&self, x: f64, ys: &[f64]) -> Option<(f64, Vec<f64>)> {
+        let x = self.x_scale.transform(x);
+        x.is_finite()
+            .then(|| (x, ys.iter().map(|&y| self.y_scale.transform(y)).collect()))
+    }
+
+    // Positions of the points that can be shown on the chart
+    pub fn positions(&self, points: impl IntoIterator<Item = (f64, Vec<f64>)>) -> Vec<(f64, Vec<f64>)> {
+        points
+            .into_iter()
+            .filter_map(|(x, ys)| self.position(x, &ys))
+            .collect()
+    }
+
+    // Positions of the points, with the curves clipped to the limits of the y axis
+    pub fn clipped_positions(&self, points: impl IntoIterator<Item = (f64, Vec<f64>)>) -> Vec<(f64, Vec<f64>)> {
+        let (y_min, y_max) = self.y_bounds();
+        clip(&self.positions(points), &Bounds::y(y_min, y_max))
+    }
+}
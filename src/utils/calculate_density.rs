@@ -1,4 +1,4 @@
-fn factor_kpc_scaled() -> f64 {
+pub fn factor_kpc_scaled() -> f64 {
     3.0875 * 10.0_f64.powi(21)
 }
 
@@ -1,4 +1,26 @@
+use scilib::constant::G;
 use std::f64::consts::PI;
+use crate::utils::{
+    calculate_density::{self, factor_kpc_scaled},
+    calculate_velocity::kpc_to_m,
+    quadrature::integrate,
+};
+
+// Convert units of the halo mass to 10^10 M☉
+pub fn halo_factor() -> f64 {
+    (3.09 * 10.0_f64.powi(18)) / 2.0
+}
+
+// Convert units of the disk mass to 10^10 M☉
+pub fn disk_factor() -> f64 {
+    10.0_f64.powi(-1)
+}
+
+// Mass in 10^10 M☉ within a radius (kpc) of a sphere with the circular velocity (km/s) there,
+// the inverse of the halo velocity
+pub fn dynamical_mass(radius: f64, velocity: f64) -> f64 {
+    velocity.powi(2) * 10.0_f64.powi(6) * radius / (G * kpc_to_m().powi(2)) * halo_factor()
+}
 
 pub fn mass_halo(radius: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool) -> f64 {
     if iso_nfw {
@@ -8,6 +30,39 @@ pub fn mass_halo(radius: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool)
     }
 }
 
+// Squared eccentricity of an ellipsoidal halo with axis ratio q, negative for prolate halos (q > 1)
+pub fn halo_eccentricity_squared(axis_ratio: f64) -> f64 {
+    1.0 - axis_ratio.powi(2)
+}
+
+// Mass within the ellipsoid with semi-major axis `radius` of a halo whose density is constant on
+// ellipsoids m² = R² + z²/q², oblate for q < 1 and prolate for q > 1
+pub fn mass_halo_ellipsoidal(radius: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool, axis_ratio: f64) -> f64 {
+    axis_ratio * mass_halo(radius, density_halo, scale_halo, iso_nfw)
+}
+
+// Mass which gives the circular velocity in the midplane of an ellipsoidal halo as v² = G·M/R,
+// from v² = 4πGq ∫₀ᴿ ρ(m) m² / √(R² - e²m²) dm (Binney & Tremaine, eq. 2.132)
+pub fn midplane_mass_halo(radius: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool, axis_ratio: f64) -> f64 {
+    // In kg/m³, zero at the center where the NFW density diverges
+    let density = |m: f64| {
+        if m == 0.0 {
+            0.0
+        } else {
+            calculate_density::density_halo(m, density_halo, scale_halo, iso_nfw) / factor_kpc_scaled()
+        }
+    };
+    midplane_mass(radius, density, axis_ratio, 0.0, radius)
+}
+
+// Midplane mass of the shells between the semi-major axes lower and upper ≤ R for a density in kg/m³;
+// shells beyond R do not act on the midplane at R
+pub fn midplane_mass(radius: f64, density: impl Fn(f64) -> f64, axis_ratio: f64, lower: f64, upper: f64) -> f64 {
+    let eccentricity_squared = halo_eccentricity_squared(axis_ratio);
+    let integrand = |m: f64| density(m) * m.powi(2) * radius / (radius.powi(2) - eccentricity_squared * m.powi(2)).sqrt();
+    4.0 * PI * axis_ratio * integrate(integrand, lower, upper.min(radius))
+}
+
 pub fn mass_disk(radius: f64, density_disk: f64, scale_disk: f64) -> f64 {
     let d: f64 = 1.0 / 2.0;
     2.0 * PI * density_disk * scale_disk * d * (scale_disk - (radius + scale_disk) * (-radius / scale_disk).exp())
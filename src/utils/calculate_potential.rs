@@ -0,0 +1,118 @@
+use scilib::constant::G;
+use std::f64::consts::PI;
+use crate::utils::{
+    bessel::{besseli, besselk},
+    calculate_density::{density_halo, factor_kpc_scaled},
+    calculate_mass::halo_eccentricity_squared,
+    quadrature::{integrate, integrate_to_infinity},
+};
+
+// The potential of the isothermal sphere diverges, so it is set to zero at this radius (kpc)
+pub const ISO_REFERENCE_RADIUS: f64 = 200.0;
+
+// Convert G * density * kpc² to (km/s)²
+pub fn kpc2_to_km2() -> f64 {
+    (3.0857 * (10.0_f64.powi(16)) * 1000.0).powi(2) / 10.0_f64.powi(6)
+}
+
+// Convert G * surface density * kpc to (km/s)²
+pub fn kpc_to_km2() -> f64 {
+    3.0857 * (10.0_f64.powi(16)) * 1000.0 / 10.0_f64.powi(6)
+}
+
+// Calculate the potential of the disk in the plane (Freeman disk)
+pub fn potential_disk(radius: f64, density_disk: f64, scale_disk: f64) -> f64 {
+    if radius == 0.0 {
+        return -2.0 * PI * G * density_disk * scale_disk * kpc_to_km2();
+    }
+    let y = radius / (2.0 * scale_disk);
+    -PI * G * density_disk * radius * (besseli(y, 0) * besselk(y, 1) - besseli(y, 1) * besselk(y, 0)) * kpc_to_km2()
+}
+
+// Calculate the potential of the halo
+pub fn potential_halo(radius: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool) -> f64 {
+    let x = radius / scale_halo;
+    if iso_nfw {
+        let f = |x: f64| if x == 0.0 { 1.0 } else { x.atan() / x + 0.5 * (1.0 + x.powi(2)).ln() };
+        -4.0 * PI * G * density_halo * scale_halo.powi(2) * (f(ISO_REFERENCE_RADIUS / scale_halo) - f(x)) * kpc2_to_km2()
+    } else {
+        let ln_term = if x == 0.0 { 1.0 } else { (1.0 + x).ln() / x };
+        -4.0 * PI * G * density_halo * scale_halo.powi(2) * ln_term * kpc2_to_km2()
+    }
+}
+
+// ∫_lower^∞ dR' / (R' √(R'² - e²m²)), the potential in the midplane beyond the shell m per unit mass
+fn midplane_tail(lower: f64, m: f64, eccentricity_squared: f64) -> f64 {
+    if lower.is_infinite() {
+        return 0.0;
+    }
+    let k = eccentricity_squared.abs().sqrt() * m;
+    if k == 0.0 {
+        1.0 / lower
+    } else if eccentricity_squared > 0.0 {
+        (k / lower).asin() / k
+    } else {
+        (k / lower).asinh() / k
+    }
+}
+
+// Calculate the potential of an ellipsoidal halo in its midplane, zero at the same radius as the spherical one.
+// Φ(R) = -G ∫ M(R') / R'² dR' with the midplane mass M, the order of the integrals swapped
+// so only one remains: ∫_A^∞ dR' / (R' √(R'² - e²m²)) has a closed form.
+pub fn potential_halo_ellipsoidal(radius: f64, density: f64, scale_halo: f64, iso_nfw: bool, axis_ratio: f64) -> f64 {
+    let eccentricity_squared = halo_eccentricity_squared(axis_ratio);
+    let reference = if iso_nfw { ISO_REFERENCE_RADIUS } else { f64::INFINITY };
+    let tail = |lower: f64, m: f64| midplane_tail(lower, m, eccentricity_squared);
+
+    // Density in kg/m³ times m², zero at the center where the NFW density diverges
+    let weight = |m: f64| {
+        if m == 0.0 {
+            0.0
+        } else {
+            density_halo(m, density, scale_halo, iso_nfw) / factor_kpc_scaled() * m.powi(2)
+        }
+    };
+
+    // ∫_lower^upper M(R') / R'² dR' for lower < upper
+    let between = |lower: f64, upper: f64| {
+        let inside = integrate(|m| weight(m) * (tail(lower, m) - tail(upper, m)), 0.0, lower);
+        let outside = |m: f64| weight(m) * (tail(m, m) - tail(upper, m));
+        let outside = if upper.is_infinite() {
+            integrate_to_infinity(outside, lower, lower.max(1.0))
+        } else {
+            integrate(outside, lower, upper)
+        };
+        4.0 * PI * axis_ratio * (inside + outside)
+    };
+
+    let integral = if radius <= reference {
+        -between(radius, reference)
+    } else {
+        between(reference, radius)
+    };
+    G * integral * kpc2_to_km2()
+}
+
+// Potential in the midplane of the ellipsoidal shells between the semi-major axes lower and upper
+// (possibly infinite) for a density in kg/m³, zero at infinity
+pub fn potential_ellipsoidal_shells(radius: f64, density: impl Fn(f64) -> f64, axis_ratio: f64, lower: f64, upper: f64) -> f64 {
+    let eccentricity_squared = halo_eccentricity_squared(axis_ratio);
+    let weight = |m: f64| density(m) * m.powi(2);
+
+    // Shells within R act from R outwards, those beyond from their own midplane radius
+    let inner = if radius > lower {
+        integrate(|m| weight(m) * midplane_tail(radius, m, eccentricity_squared), lower, radius.min(upper))
+    } else {
+        0.0
+    };
+    let start = radius.max(lower);
+    let outer = |m: f64| weight(m) * midplane_tail(m, m, eccentricity_squared);
+    let outer = if upper.is_infinite() {
+        integrate_to_infinity(outer, start, start.max(1.0))
+    } else if start < upper {
+        integrate(outer, start, upper)
+    } else {
+        0.0
+    };
+    -G * 4.0 * PI * axis_ratio * (inner + outer) * kpc2_to_km2()
+}
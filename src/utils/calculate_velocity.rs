@@ -1,8 +1,8 @@
 use scilib::constant::G;
 use std::f64::consts::PI;
-use crate::utils::{calculate_mass::mass_halo, bessel::{besseli, besselk}};
+use crate::utils::{calculate_mass::{mass_halo, midplane_mass_halo}, bessel::{besseli, besselk}};
 
-fn kpc_to_m() -> f64 {
+pub fn kpc_to_m() -> f64 {
     3.0857 * (10.0_f64.powi(16)) * 1000.0
 }
 
@@ -10,6 +10,11 @@ fn m_s_to_km_s() -> f64 {
     1.0 / 1000.0
 }
 
+// Convert kpc / (km/s) to Myr
+pub fn kpc_per_km_s_to_myr() -> f64 {
+    3.0857 * (10.0_f64.powi(16)) / (3.15576 * 10.0_f64.powi(13))
+}
+
 // Calculate the combined velocity of the disk and halo
 pub fn calculate_velocity(radius: f64, density_disk: f64, scale_disk: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool) -> f64 {
     if radius == 0.0 {return 0.0;}
@@ -17,14 +22,20 @@ pub fn calculate_velocity(radius: f64, density_disk: f64, scale_disk: f64, densi
 }
 
 //Calculate the velocity of the disk
-fn velocity_disk(radius: f64, density_disk: f64, scale_disk: f64) -> f64 {
+pub fn velocity_disk(radius: f64, density_disk: f64, scale_disk: f64) -> f64 {
     let gamma = radius / (2.0 * scale_disk);
     let v_disk = (4.0 * PI * G * density_disk * scale_disk * gamma.powi(2) * (besseli(gamma, 0) * besselk(gamma, 0) - besseli(gamma, 1) * besselk(gamma, 1))).sqrt();
     v_disk * (kpc_to_m()).sqrt() * m_s_to_km_s()
 }
 
 //Calculate the velocity of the halo
-fn velocity_halo(radius: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool) -> f64 {
+pub fn velocity_halo(radius: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool) -> f64 {
     let v_halo = (G * mass_halo(radius, density_halo, scale_halo, iso_nfw) / radius).sqrt();
     v_halo * kpc_to_m() * m_s_to_km_s()
-}
\ No newline at end of file
+}
+
+//Calculate the velocity of an ellipsoidal halo in its midplane
+pub fn velocity_halo_ellipsoidal(radius: f64, density_halo: f64, scale_halo: f64, iso_nfw: bool, axis_ratio: f64) -> f64 {
+    let v_halo = (G * midplane_mass_halo(radius, density_halo, scale_halo, iso_nfw, axis_ratio) / radius).sqrt();
+    v_halo * kpc_to_m() * m_s_to_km_s()
+}
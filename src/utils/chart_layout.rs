@@ -0,0 +1,109 @@
+// Layout of the charts in px. It is set explicitly and the tick labels of the y axis have a fixed
+// width, so a click can be mapped onto the plot from the size of the chart alone.
+use crate::utils::axis_settings::AxisScale;
+
+pub const FONT_HEIGHT: f64 = 16.0;
+pub const FONT_WIDTH: f64 = 10.0;
+pub const PADDING: f64 = 8.0;
+// Width of the tick labels of the y axis in characters, longer labels are shortened to it
+pub const Y_TICK_CHARS: usize = 7;
+
+// Labels with more decimals are written in scientific notation
+const MAX_DECIMALS: usize = 3;
+const MAX_EXPONENT_DECIMALS: usize = 2;
+// Largest relative deviation of a label from its value
+const LABEL_TOLERANCE: f64 = 1.0e-6;
+
+// Horizontal distances of the plot from the left and right edge of the chart. The left side holds
+// the rotated label and the tick labels, each padded on both sides, the chart itself is padded once.
+pub fn plot_insets() -> (f64, f64) {
+    let label = FONT_HEIGHT + 2.0 * PADDING;
+    let ticks = Y_TICK_CHARS as f64 * FONT_WIDTH + 2.0 * PADDING;
+    (PADDING + label + ticks, PADDING)
+}
+
+// Position of a point x px right of the left edge of a chart along the x axis of its plot,
+// from 0 at the left to 1 at the right edge; None outside of the plot
+pub fn plot_position(x: f64, chart_width: f64) -> Option<f64> {
+    let (left, right) = plot_insets();
+    let width = chart_width - left - right;
+    if width <= 0.0 {
+        return None;
+    }
+
+    let position = (x - left) / width;
+    (0.0..=1.0).contains(&position).then_some(position)
+}
+
+// Label of a tick of the y axis with at most Y_TICK_CHARS characters: the shortest one showing
+// the value, otherwise the value rounded in scientific notation
+pub fn y_tick_label(scale: AxisScale, position: f64) -> String {
+    let value = scale.inverse(position);
+    let fits = |label: &String| label.chars().count() <= Y_TICK_CHARS;
+
+    // The labels of log axes are already rounded
+    let label = scale.format(position, 2);
+    if scale == AxisScale::Logarithmic && fits(&label) {
+        return label;
+    }
+
+    let decimal = (0..=MAX_DECIMALS).map(|decimals| format!("{:.*}", decimals, value));
+    let scientific = (0..=MAX_EXPONENT_DECIMALS).map(|decimals| format!("{:.*e}", decimals, value));
+    let candidates: Vec<String> = decimal.chain(scientific).filter(fits).collect();
+    let exact = |label: &&String| {
+        label
+            .parse::<f64>()
+            .is_ok_and(|shown| (shown - value).abs() <= LABEL_TOLERANCE * value.abs())
+    };
+
+    candidates
+        .iter()
+        .find(exact)
+        // Most precise scientific notation that fits
+        .or(candidates.last())
+        .cloned()
+        .unwrap_or(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_map_onto_the_plot() {
+        // 8 px padding, 32 px rotated label and 86 px tick labels on the left, 8 px padding on the right
+        assert_eq!(plot_insets(), (126.0, 8.0));
+
+        let width = 1134.0;
+        assert_eq!(plot_position(126.0, width), Some(0.0));
+        assert_eq!(plot_position(626.0, width), Some(0.5));
+        assert_eq!(plot_position(1126.0, width), Some(1.0));
+
+        // On the labels or the padding
+        assert_eq!(plot_position(100.0, width), None);
+        assert_eq!(plot_position(1130.0, width), None);
+        // No room for a plot
+        assert_eq!(plot_position(130.0, 120.0), None);
+    }
+
+    #[test]
+    fn y_tick_labels_have_a_fixed_width() {
+        let linear = |value| y_tick_label(AxisScale::Linear, value);
+        assert_eq!(linear(0.0), "0");
+        assert_eq!(linear(250.0), "250");
+        assert_eq!(linear(-0.125), "-0.125");
+        assert_eq!(linear(-120000.0), "-120000");
+        assert_eq!(linear(12345678.0), "1.23e7");
+        assert_eq!(linear(1.5e-22), "1.5e-22");
+        assert_eq!(linear(-1.25e-22), "-1e-22");
+
+        let logarithmic = |value: f64| y_tick_label(AxisScale::Logarithmic, value.log10());
+        assert_eq!(logarithmic(100.0), "100");
+        assert_eq!(logarithmic(0.05), "0.05");
+        assert_eq!(logarithmic(1.5e-22), "1.5e-22");
+
+        for value in [1.0e-300, -1.0e-300, 123.456789, -9.87654321e12, f64::MAX] {
+            assert!(linear(value).chars().count() <= Y_TICK_CHARS, "{}", linear(value));
+        }
+    }
+}
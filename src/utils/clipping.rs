@@ -0,0 +1,185 @@
+// Clipping of chart curves to a rectangle.
+// Curves leaving the rectangle end exactly at its edge and start again where they re-enter,
+// instead of stopping at the last sample inside it.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub x_min: f64,
+    pub x_max: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+}
+
+impl Bounds {
+    // Limits on the y axis only, None for no limit
+    pub fn y(y_min: Option<f64>, y_max: Option<f64>) -> Self {
+        Self {
+            x_min: f64::NEG_INFINITY,
+            x_max: f64::INFINITY,
+            y_min: y_min.unwrap_or(f64::NEG_INFINITY),
+            y_max: y_max.unwrap_or(f64::INFINITY),
+        }
+    }
+
+    pub fn with_x(self, x_min: f64, x_max: f64) -> Self {
+        Self { x_min, x_max, ..self }
+    }
+
+    fn contains_x(&self, x: f64) -> bool {
+        x >= self.x_min && x <= self.x_max
+    }
+
+    fn contains_y(&self, y: f64) -> bool {
+        y >= self.y_min && y <= self.y_max
+    }
+}
+
+// Edge of the bounds crossed between two points
+#[derive(Clone, Copy)]
+enum Edge {
+    Vertical(f64),
+    // Curve crossing and value of the edge
+    Horizontal(usize, f64),
+}
+
+// Position (0 to 1) where the segment from a to b crosses a value, if it does strictly inside
+fn crossing(a: f64, b: f64, value: f64) -> Option<f64> {
+    if !a.is_finite() || !b.is_finite() || !value.is_finite() || a == b {
+        return None;
+    }
+    let t = (value - a) / (b - a);
+    (t > 0.0 && t < 1.0).then_some(t)
+}
+
+// Clip points with ascending x and one value per curve.
+// Points are added where any curve crosses an edge, with the other curves interpolated there;
+// values outside the bounds become NaN, so the chart leaves a gap.
+pub fn clip(points: &[(f64, Vec<f64>)], bounds: &Bounds) -> Vec<(f64, Vec<f64>)> {
+    let mut clipped = Vec::with_capacity(points.len());
+
+    for (i, (x1, ys1)) in points.iter().enumerate() {
+        if let Some((x0, ys0)) = i.checked_sub(1).map(|prev| &points[prev]) {
+            // Crossings of the curves with the horizontal edges and of the segment with the vertical ones
+            let mut crossings: Vec<(f64, Edge)> = ys0
+                .iter()
+                .zip(ys1)
+                .enumerate()
+                .flat_map(|(k, (&y0, &y1))| {
+                    [bounds.y_min, bounds.y_max]
+                        .into_iter()
+                        .filter_map(move |edge| crossing(y0, y1, edge).map(|t| (t, Edge::Horizontal(k, edge))))
+                })
+                .chain(
+                    [bounds.x_min, bounds.x_max]
+                        .into_iter()
+                        .filter_map(|edge| crossing(*x0, *x1, edge).map(|t| (t, Edge::Vertical(edge)))),
+                )
+                .collect();
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            for (t, edge) in crossings {
+                // Exactly on the crossed edge, so rounding can not move the point outside
+                let x = match edge {
+                    Edge::Vertical(value) => value,
+                    Edge::Horizontal(..) => x0 + t * (x1 - x0),
+                };
+                let ys = ys0
+                    .iter()
+                    .zip(ys1)
+                    .enumerate()
+                    .map(|(k, (y0, y1))| match edge {
+                        Edge::Horizontal(curve, value) if curve == k => value,
+                        _ => y0 + t * (y1 - y0),
+                    })
+                    .collect();
+                clipped.push((x, ys));
+            }
+        }
+
+        clipped.push((*x1, ys1.clone()));
+    }
+
+    // Remove everything outside of the bounds
+    for (x, ys) in clipped.iter_mut() {
+        let inside_x = bounds.contains_x(*x);
+        for y in ys.iter_mut() {
+            if !inside_x || !bounds.contains_y(*y) {
+                *y = f64::NAN;
+            }
+        }
+    }
+
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Compares values with NaN equal to NaN
+    fn assert_points(actual: &[(f64, Vec<f64>)], expected: &[(f64, Vec<f64>)]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for ((x, ys), (expected_x, expected_ys)) in actual.iter().zip(expected) {
+            assert!((x - expected_x).abs() < 1e-12, "{:?}", actual);
+            for (y, expected_y) in ys.iter().zip(expected_ys) {
+                assert!(
+                    (y.is_nan() && expected_y.is_nan()) || (y - expected_y).abs() < 1e-12,
+                    "{:?}",
+                    actual
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn curve_leaves_and_reenters() {
+        let points = vec![(0.0, vec![0.0]), (1.0, vec![2.0]), (2.0, vec![0.0])];
+        let clipped = clip(&points, &Bounds::y(None, Some(1.0)));
+        assert_points(
+            &clipped,
+            &[
+                (0.0, vec![0.0]),
+                (0.5, vec![1.0]),
+                (1.0, vec![f64::NAN]),
+                (1.5, vec![1.0]),
+                (2.0, vec![0.0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn other_curves_are_interpolated() {
+        let points = vec![(0.0, vec![0.0, 0.5]), (2.0, vec![4.0, 1.5])];
+        let clipped = clip(&points, &Bounds::y(Some(0.0), Some(2.0)));
+        assert_points(
+            &clipped,
+            &[(0.0, vec![0.0, 0.5]), (1.0, vec![2.0, 1.0]), (2.0, vec![f64::NAN, 1.5])],
+        );
+    }
+
+    #[test]
+    fn x_bounds() {
+        let points = vec![(0.0, vec![0.0]), (2.0, vec![2.0]), (4.0, vec![4.0])];
+        let clipped = clip(&points, &Bounds::y(None, None).with_x(1.0, 3.0));
+        assert_points(
+            &clipped,
+            &[
+                (0.0, vec![f64::NAN]),
+                (1.0, vec![1.0]),
+                (2.0, vec![2.0]),
+                (3.0, vec![3.0]),
+                (4.0, vec![f64::NAN]),
+            ],
+        );
+    }
+
+    #[test]
+    fn nan_values_are_kept_without_crossings() {
+        let points = vec![(0.0, vec![f64::NAN]), (1.0, vec![5.0]), (2.0, vec![0.0])];
+        let clipped = clip(&points, &Bounds::y(None, Some(1.0)));
+        assert_points(
+            &clipped,
+            &[(0.0, vec![f64::NAN]), (1.0, vec![f64::NAN]), (1.8, vec![1.0]), (2.0, vec![0.0])],
+        );
+    }
+}
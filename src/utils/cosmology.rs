@@ -0,0 +1,98 @@
+// Cosmological reference values for the size of a halo
+use crate::utils::calculate_mass::halo_factor;
+use scilib::constant::G;
+use std::f64::consts::PI;
+
+// Hubble constant in km/s/Mpc
+pub const HUBBLE_CONSTANT: f64 = 70.0;
+
+// Density parameters of matter and dark energy, flat universe
+pub const OMEGA_MATTER: f64 = 0.3;
+pub const OMEGA_LAMBDA: f64 = 1.0 - OMEGA_MATTER;
+
+// Mean density within r₂₀₀ relative to the critical density
+pub const OVERDENSITY: f64 = 200.0;
+
+// Radii between which r₂₀₀ is searched (kpc)
+const SEARCH_RANGE: (f64, f64) = (1.0e-3, 1.0e4);
+
+// Hubble parameter at a redshift relative to today
+fn hubble_ratio(redshift: f64) -> f64 {
+    (OMEGA_MATTER * (1.0 + redshift).powi(3) + OMEGA_LAMBDA).sqrt()
+}
+
+// Critical density at a redshift in 10^10 M☉/kpc³
+pub fn critical_density_at(redshift: f64) -> f64 {
+    let hubble = HUBBLE_CONSTANT * hubble_ratio(redshift) * 1000.0 / 3.0857e22;
+    3.0 * hubble.powi(2) / (8.0 * PI * G) * halo_factor()
+}
+
+// Concentration of NFW halos from their mass M₂₀₀ (10^10 M☉), Dutton & Macciò 2014
+pub fn concentration_from_mass(mass: f64, redshift: f64) -> f64 {
+    let a = 0.520 + (0.905 - 0.520) * (-0.617 * redshift.powf(1.21)).exp();
+    let b = -0.101 + 0.026 * redshift;
+    // Pivot mass of 10^12 M☉/h
+    let pivot = 100.0 / (HUBBLE_CONSTANT / 100.0);
+    10.0_f64.powf(a + b * (mass / pivot).log10())
+}
+
+// Radius within which the mean density is 200 times the critical density at a redshift, for a mass
+// profile in 10^10 M☉; None if it is not within the search range
+pub fn r200(mass: impl Fn(f64) -> f64, redshift: f64) -> Option<f64> {
+    let target = OVERDENSITY * critical_density_at(redshift);
+    let excess = |r: f64| mass(r) / (4.0 / 3.0 * PI * r.powi(3)) - target;
+
+    let (mut low, mut high) = SEARCH_RANGE;
+    if !(excess(low) > 0.0 && excess(high) < 0.0) {
+        return None;
+    }
+
+    // Bisection in log r
+    for _ in 0..60 {
+        let mid = (low * high).sqrt();
+        if excess(mid) > 0.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low * high).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            ((actual - expected) / expected).abs() < tolerance,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn critical_density_today_and_earlier() {
+        // 3 H₀² / 8πG for H₀ = 70 km/s/Mpc in kg/m³
+        assert_close(critical_density_at(0.0) / halo_factor(), 9.204e-27, 1e-3);
+
+        // Grows with H(z)² = H₀² (Ω_m (1 + z)³ + Ω_Λ)
+        for redshift in [0.5_f64, 1.0, 3.0] {
+            let ratio = OMEGA_MATTER * (1.0 + redshift).powi(3) + OMEGA_LAMBDA;
+            assert_close(critical_density_at(redshift), ratio * critical_density_at(0.0), 1e-12);
+        }
+    }
+
+    #[test]
+    fn r200_of_a_point_mass() {
+        // All mass at the center: r₂₀₀ follows from M = 200 ρ_c 4/3 π r³
+        let mass = 100.0;
+        for redshift in [0.0, 2.0] {
+            let expected = (3.0 * mass / (4.0 * PI * OVERDENSITY * critical_density_at(redshift))).cbrt();
+            assert_close(r200(|_| mass, redshift).unwrap(), expected, 1e-9);
+        }
+
+        // Outside of the search range
+        assert_eq!(r200(|_| 0.0, 0.0), None);
+        assert_eq!(r200(|_| 1e30, 0.0), None);
+    }
+}
@@ -0,0 +1,162 @@
+// Characteristic radii and velocities of the rotation curve, found numerically
+use crate::utils::{
+    galaxy_model::GalaxyModel,
+    mass_component::total_velocity,
+    tully_fisher::flat_velocity,
+};
+
+// Radii scanned for the features, each one is then refined
+const SCAN_SAMPLES: usize = 400;
+const REFINE_STEPS: usize = 60;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CurveFeatures {
+    // Radius (kpc) and velocity (km/s) of the highest total velocity, None if the curve rises up to the maximum radius
+    pub peak: Option<(f64, f64)>,
+    // Mean velocity of the outer part of the disk (km/s), independent of the chart range
+    pub flat_velocity: f64,
+    // Radius from which the halo contributes more velocity than the disk (kpc)
+    pub crossing_radius: Option<f64>,
+    // Radius from which the halo holds more than half the enclosed mass (kpc)
+    pub half_mass_radius: Option<f64>,
+}
+
+// Radii of the scan, without the center
+fn scan_radii(max_radius: f64) -> impl Iterator<Item = f64> {
+    (1..=SCAN_SAMPLES).map(move |i| max_radius * i as f64 / SCAN_SAMPLES as f64)
+}
+
+// First radius at which the function becomes positive, refined by bisection
+fn first_crossing(f: impl Fn(f64) -> f64, max_radius: f64) -> Option<f64> {
+    let mut previous: Option<f64> = None;
+    for radius in scan_radii(max_radius) {
+        if f(radius) > 0.0 {
+            let Some(mut low) = previous else {
+                // Positive from the first radius on
+                return Some(radius);
+            };
+            let mut high = radius;
+            for _ in 0..REFINE_STEPS {
+                let middle = (low + high) / 2.0;
+                if f(middle) > 0.0 {
+                    high = middle;
+                } else {
+                    low = middle;
+                }
+            }
+            return Some((low + high) / 2.0);
+        }
+        previous = Some(radius);
+    }
+    None
+}
+
+// Highest value inside the range, refined by golden section search.
+// None if the highest scanned value is at the maximum radius.
+fn maximum(f: impl Fn(f64) -> f64, max_radius: f64) -> Option<(f64, f64)> {
+    let step = max_radius / SCAN_SAMPLES as f64;
+    let (peak_radius, _) = scan_radii(max_radius)
+        .map(|radius| (radius, f(radius)))
+        .filter(|(_, value)| value.is_finite())
+        .fold((f64::NAN, f64::NEG_INFINITY), |best, (radius, value)| if value > best.1 { (radius, value) } else { best });
+    if !peak_radius.is_finite() || peak_radius >= max_radius - step / 2.0 {
+        return None;
+    }
+
+    // Each step keeps one inner point and its value, so only the other one is evaluated
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high) = ((peak_radius - step).max(0.0), peak_radius + step);
+    let (mut a, mut b) = (high - ratio * (high - low), low + ratio * (high - low));
+    let (mut value_a, mut value_b) = (f(a), f(b));
+    for _ in 0..REFINE_STEPS {
+        if value_a < value_b {
+            low = a;
+            (a, value_a) = (b, value_b);
+            b = low + ratio * (high - low);
+            value_b = f(b);
+        } else {
+            high = b;
+            (b, value_b) = (a, value_a);
+            a = high - ratio * (high - low);
+            value_a = f(a);
+        }
+    }
+    let radius = (low + high) / 2.0;
+    Some((radius, f(radius)))
+}
+
+// Radius from which the halo holds more than half the enclosed mass, None if not within the maximum radius
+pub fn dominance_radius(model: &GalaxyModel, max_radius: f64) -> Option<f64> {
+    let dark_matter_fraction = model.dark_matter_fraction();
+    first_crossing(|radius| dark_matter_fraction(radius) - 0.5, max_radius)
+}
+
+// Features of the curve of the model up to the maximum radius of the charts
+pub fn curve_features(model: &GalaxyModel, max_radius: f64) -> CurveFeatures {
+    let components = model.disk_and_halo();
+    let [disk, halo] = &components;
+
+    CurveFeatures {
+        peak: maximum(|radius| total_velocity(&components, radius), max_radius),
+        flat_velocity: flat_velocity(&components, model.disk.scale),
+        crossing_radius: first_crossing(
+            |radius| halo.velocity(radius) - disk.velocity(radius),
+            max_radius,
+        ),
+        half_mass_radius: dominance_radius(model, max_radius),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    use crate::utils::calculate_mass::halo_factor;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            ((actual - expected) / expected).abs() < tolerance,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn peak_of_a_bare_exponential_disk() {
+        let mut model = GalaxyModel::default();
+        model.halo.density = 0.0;
+        let features = curve_features(&model, 50.0);
+
+        // The velocity of an exponential disk peaks at 2.1585 scale lengths;
+        // within the accuracy of the approximated Bessel functions
+        let (radius, velocity) = features.peak.unwrap();
+        assert_close(radius, 2.1585 * model.disk.scale, 1e-2);
+        assert_close(velocity, total_velocity(&model.components(), radius), 1e-12);
+
+        // Without a halo nothing crosses
+        assert_eq!(features.crossing_radius, None);
+        assert_eq!(features.half_mass_radius, None);
+    }
+
+    #[test]
+    fn crossing_and_half_mass_radii() {
+        let model = GalaxyModel::default();
+        let features = curve_features(&model, 100.0);
+        let (disk, halo) = (&model.disk, &model.halo);
+
+        // Enclosed masses of the exponential disk and the isothermal halo (10^10 M☉)
+        let disk_mass = |r: f64| PI * disk.density * disk.scale * (disk.scale - (r + disk.scale) * (-r / disk.scale).exp()) / 10.0;
+        let halo_mass = |r: f64| 4.0 * PI * halo.density * halo.scale.powi(2) * (r - halo.scale * (r / halo.scale).atan()) * halo_factor();
+
+        // The halo dominates from where it holds half of the mass
+        let half_mass_radius = features.half_mass_radius.unwrap();
+        assert_eq!(dominance_radius(&model, 100.0), Some(half_mass_radius));
+        assert_close(halo_mass(half_mass_radius), disk_mass(half_mass_radius), 1e-6);
+        assert!(halo_mass(0.9 * half_mass_radius) < disk_mass(0.9 * half_mass_radius));
+
+        // Equal velocities where the halo overtakes the disk
+        let components = model.components();
+        let crossing_radius = features.crossing_radius.unwrap();
+        assert_close(components[1].velocity(crossing_radius), components[0].velocity(crossing_radius), 1e-6);
+        assert!(components[1].velocity(0.9 * crossing_radius) < components[0].velocity(0.9 * crossing_radius));
+    }
+}
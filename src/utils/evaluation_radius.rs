@@ -0,0 +1,37 @@
+// Radius at which the masses are compared
+use crate::utils::{cosmology::r200, galaxy_model::GalaxyModel};
+
+// Optical radius in disk scale lengths, enclosing about 83% of the light of an exponential disk
+pub const OPTICAL_RADIUS_SCALES: f64 = 3.2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EvaluationRadius {
+    // Radius in kpc
+    Fixed(f64),
+    Optical,
+    R200,
+}
+
+impl Default for EvaluationRadius {
+    fn default() -> Self {
+        Self::Fixed(30.0)
+    }
+}
+
+impl EvaluationRadius {
+    // Radius in kpc for the model, presets follow the parameters; None if there is no r₂₀₀
+    pub fn resolve(&self, model: &GalaxyModel) -> Option<f64> {
+        match self {
+            Self::Fixed(radius) => Some(*radius),
+            Self::Optical => Some(OPTICAL_RADIUS_SCALES * model.disk.scale),
+            Self::R200 => {
+                let components = model.components();
+                // Ellipsoidal halos are compared by the mean density within their ellipsoids
+                r200(
+                    |r| components.iter().map(|component| component.mass(r) / component.axis_ratio()).sum(),
+                    model.redshift(),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,503 @@
+// Parser and evaluator for density functions typed in by the user, e.g. "rho0 / (1 + (r/a)^2)"
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Exp,
+    Ln,
+    Log,
+    Sqrt,
+    Abs,
+    Sin,
+    Cos,
+    Tan,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "exp" => Self::Exp,
+            "ln" => Self::Ln,
+            "log" => Self::Log,
+            "sqrt" => Self::Sqrt,
+            "abs" => Self::Abs,
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "tan" => Self::Tan,
+            "atan" | "arctan" => Self::Atan,
+            "sinh" => Self::Sinh,
+            "cosh" => Self::Cosh,
+            "tanh" => Self::Tanh,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, x: f64) -> f64 {
+        match self {
+            Self::Exp => x.exp(),
+            Self::Ln => x.ln(),
+            Self::Log => x.log10(),
+            Self::Sqrt => x.sqrt(),
+            Self::Abs => x.abs(),
+            Self::Sin => x.sin(),
+            Self::Cos => x.cos(),
+            Self::Tan => x.tan(),
+            Self::Atan => x.atan(),
+            Self::Sinh => x.sinh(),
+            Self::Cosh => x.cosh(),
+            Self::Tanh => x.tanh(),
+        }
+    }
+
+    fn to_tex(self, argument: &str) -> String {
+        let command = match self {
+            Self::Exp => return format!("e^{{{}}}", argument),
+            Self::Sqrt => return format!(r"\sqrt{{{}}}", argument),
+            Self::Abs => return format!(r"\left|{}\right|", argument),
+            Self::Ln => r"\ln",
+            Self::Log => r"\log_{10}",
+            Self::Sin => r"\sin",
+            Self::Cos => r"\cos",
+            Self::Tan => r"\tan",
+            Self::Atan => r"\arctan",
+            Self::Sinh => r"\sinh",
+            Self::Cosh => r"\cosh",
+            Self::Tanh => r"\tanh",
+        };
+        format!(r"{}\left({}\right)", command, argument)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expression {
+    Number(f64),
+    // Index into `Formula::variables`
+    Variable(usize),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Box<Expression>),
+}
+
+impl Expression {
+    fn evaluate(&self, variable: &impl Fn(usize) -> f64) -> f64 {
+        match self {
+            Self::Number(value) => *value,
+            Self::Variable(index) => variable(*index),
+            Self::Negate(inner) => -inner.evaluate(variable),
+            Self::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(variable), right.evaluate(variable));
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Power => left.powf(right),
+                }
+            }
+            Self::Call(function, argument) => function.apply(argument.evaluate(variable)),
+        }
+    }
+
+    // Binding strength, used to decide where the TeX output needs parentheses
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(Operator::Add | Operator::Subtract, ..) => 1,
+            Self::Binary(Operator::Multiply | Operator::Divide, ..) => 2,
+            Self::Negate(_) => 3,
+            Self::Binary(Operator::Power, ..) => 4,
+            Self::Number(value) if *value < 0.0 => 3,
+            _ => 5,
+        }
+    }
+
+    fn to_tex(&self, variables: &[String]) -> String {
+        let wrap = |expression: &Expression, min_precedence: u8| {
+            let tex = expression.to_tex(variables);
+            if expression.precedence() < min_precedence {
+                format!(r"\left({}\right)", tex)
+            } else {
+                tex
+            }
+        };
+
+        match self {
+            Self::Number(value) => number_to_tex(*value),
+            Self::Variable(index) => variable_to_tex(&variables[*index]),
+            Self::Negate(inner) => format!("-{}", wrap(inner, 2)),
+            Self::Binary(operator, left, right) => match operator {
+                Operator::Add => format!("{}+{}", wrap(left, 1), wrap(right, 1)),
+                Operator::Subtract => format!("{}-{}", wrap(left, 1), wrap(right, 2)),
+                Operator::Multiply => format!(r"{} \cdot {}", wrap(left, 2), wrap(right, 2)),
+                Operator::Divide => format!(
+                    r"\frac{{{}}}{{{}}}",
+                    left.to_tex(variables),
+                    right.to_tex(variables)
+                ),
+                Operator::Power => format!("{{{}}}^{{{}}}", wrap(left, 5), right.to_tex(variables)),
+            },
+            Self::Call(function, argument) => function.to_tex(&argument.to_tex(variables)),
+        }
+    }
+}
+
+fn number_to_tex(value: f64) -> String {
+    let text = value.to_string();
+    if value != 0.0 && (value.abs() < 1.0e-3 || value.abs() >= 1.0e6) {
+        let formatted = format!("{:e}", value);
+        let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+        format!(r"{} \cdot 10^{{{}}}", mantissa, exponent)
+    } else {
+        text
+    }
+}
+
+fn variable_to_tex(name: &str) -> String {
+    const GREEK: [&str; 12] = [
+        "alpha", "beta", "gamma", "delta", "epsilon", "kappa", "lambda", "mu", "nu", "sigma",
+        "tau", "omega",
+    ];
+
+    if name == "rho0" {
+        return r"\rho_{0}".to_string();
+    }
+
+    let (base, subscript) = match name.split_once('_') {
+        Some((base, subscript)) => (base, Some(subscript)),
+        None => (name, None),
+    };
+    let base = if GREEK.contains(&base) {
+        format!(r"\{}", base)
+    } else if base.chars().count() > 1 {
+        format!(r"\mathrm{{{}}}", base)
+    } else {
+        base.to_string()
+    };
+
+    match subscript {
+        Some(subscript) => format!("{}_{{{}}}", base, subscript),
+        None => base,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(char),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "{}", value),
+            Self::Identifier(name) => write!(f, "{}", name),
+            Self::Operator(c) => write!(f, "{}", c),
+            Self::Open => write!(f, "("),
+            Self::Close => write!(f, ")"),
+        }
+    }
+}
+
+// Greek letters and subscripts are accepted for the central density
+fn normalize_identifier(name: &str) -> String {
+    match name {
+        "ρ₀" | "ρ0" | "ρ_0" | "rho_0" | "rho0" => "rho0".to_string(),
+        "π" => "pi".to_string(),
+        _ => name.to_string(),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent, e.g. 1.5e-21
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid number \"{}\"", text))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '₀')
+            {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            tokens.push(Token::Identifier(normalize_identifier(&name)));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '/' | '^' => Token::Operator(c),
+                '*' | '·' | '×' => Token::Operator('*'),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                _ => return Err(format!("Unexpected character \"{}\"", c)),
+            });
+            i += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Recursive descent parser, the grammar is
+// sum = product (("+" | "-") product)*
+// product = unary (("*" | "/") unary)*
+// unary = ("-" | "+") unary | power
+// power = atom ("^" unary)?
+// atom = number | name | function "(" sum ")" | "(" sum ")"
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn sum(&mut self) -> Result<Expression, String> {
+        let mut left = self.product()?;
+        while let Some(Token::Operator(c @ ('+' | '-'))) = self.peek() {
+            let operator = if *c == '+' {
+                Operator::Add
+            } else {
+                Operator::Subtract
+            };
+            self.position += 1;
+            left = Expression::Binary(operator, Box::new(left), Box::new(self.product()?));
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<Expression, String> {
+        let mut left = self.unary()?;
+        while let Some(Token::Operator(c @ ('*' | '/'))) = self.peek() {
+            let operator = if *c == '*' {
+                Operator::Multiply
+            } else {
+                Operator::Divide
+            };
+            self.position += 1;
+            left = Expression::Binary(operator, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some(Token::Operator('-')) => {
+                self.position += 1;
+                Ok(Expression::Negate(Box::new(self.unary()?)))
+            }
+            Some(Token::Operator('+')) => {
+                self.position += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Expression, String> {
+        let base = self.atom()?;
+        if let Some(Token::Operator('^')) = self.peek() {
+            self.position += 1;
+            // Right associative: a^b^c = a^(b^c)
+            let exponent = self.unary()?;
+            return Ok(Expression::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::Open) => {
+                let inner = self.sum()?;
+                self.expect_close()?;
+                Ok(inner)
+            }
+            Some(Token::Identifier(name)) => {
+                if let Some(function) = Function::from_name(&name) {
+                    if self.next() != Some(Token::Open) {
+                        return Err(format!("Expected \"(\" after {}", name));
+                    }
+                    let argument = self.sum()?;
+                    self.expect_close()?;
+                    return Ok(Expression::Call(function, Box::new(argument)));
+                }
+                if name == "pi" {
+                    return Ok(Expression::Number(PI));
+                }
+
+                let index = match self.variables.iter().position(|variable| *variable == name) {
+                    Some(index) => index,
+                    None => {
+                        self.variables.push(name);
+                        self.variables.len() - 1
+                    }
+                };
+                Ok(Expression::Variable(index))
+            }
+            Some(Token::Operator(c)) => Err(format!("Unexpected operator \"{}\"", c)),
+            Some(Token::Close) => Err("Unexpected \")\"".to_string()),
+            None => Err("Unexpected end of the formula".to_string()),
+        }
+    }
+
+    fn expect_close(&mut self) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Close) => Ok(()),
+            _ => Err("Missing \")\"".to_string()),
+        }
+    }
+}
+
+// A parsed formula together with the names of its variables
+#[derive(Clone, Debug, PartialEq)]
+pub struct Formula {
+    expression: Expression,
+    variables: Vec<String>,
+}
+
+impl Formula {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+            variables: Vec::new(),
+        };
+
+        let expression = parser.sum()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected \"{}\" after the end of the formula", token));
+        }
+
+        Ok(Self {
+            expression,
+            variables: parser.variables,
+        })
+    }
+
+    // Names of all variables, in the order of their first appearance
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    // Evaluate with the value of each variable given by its index in `variables`
+    pub fn evaluate(&self, variable: impl Fn(usize) -> f64) -> f64 {
+        self.expression.evaluate(&variable)
+    }
+
+    pub fn to_tex(&self) -> String {
+        self.expression.to_tex(&self.variables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Value of a formula without variables
+    fn value(source: &str) -> f64 {
+        Formula::parse(source).unwrap().evaluate(|_| f64::NAN)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= 1e-12 * expected.abs().max(1.0), "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn precedence() {
+        assert_close(value("1 + 2 * 3"), 7.0);
+        assert_close(value("(1 + 2) * 3"), 9.0);
+        assert_close(value("8 / 4 / 2"), 1.0);
+        assert_close(value("10 - 4 - 3"), 3.0);
+        assert_close(value("2 * 3 ^ 2"), 18.0);
+        assert_close(value("2 ^ 3 ^ 2"), 512.0);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_close(value("-2 ^ 2"), -4.0);
+        assert_close(value("(-2) ^ 2"), 4.0);
+        assert_close(value("2 ^ -1"), 0.5);
+        assert_close(value("3 * -2"), -6.0);
+        assert_close(value("--3 + +1"), 4.0);
+    }
+
+    #[test]
+    fn numbers_functions_and_constants() {
+        assert_close(value("1.5e-2 * 2E+2"), 3.0);
+        assert_close(value("sqrt(16) + ln(exp(2)) + log(1000)"), 9.0);
+        assert_close(value("cos(π) + 2 × pi / pi"), 1.0);
+    }
+
+    #[test]
+    fn variables_in_order_of_appearance() {
+        let formula = Formula::parse("ρ₀ / (1 + (r / a)^2) + rho0 * 0").unwrap();
+        assert_eq!(formula.variables(), ["rho0", "r", "a"]);
+        let values = [4.0, 2.0, 2.0];
+        assert_close(formula.evaluate(|i| values[i]), 2.0);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Formula::parse("1 +"), Err("Unexpected end of the formula".to_string()));
+        assert_eq!(Formula::parse("(1 + 2"), Err("Missing \")\"".to_string()));
+        assert_eq!(Formula::parse("1 + 2)"), Err("Unexpected \")\" after the end of the formula".to_string()));
+        assert_eq!(Formula::parse("2 * / 3"), Err("Unexpected operator \"/\"".to_string()));
+        assert_eq!(Formula::parse("exp 2"), Err("Expected \"(\" after exp".to_string()));
+        assert_eq!(Formula::parse("1 $ 2"), Err("Unexpected character \"$\"".to_string()));
+        assert_eq!(Formula::parse("1.2.3"), Err("Invalid number \"1.2.3\"".to_string()));
+    }
+}
@@ -0,0 +1,225 @@
+// Fit the model parameters to an observed rotation curve
+use crate::utils::{
+    galaxy_model::GalaxyModel, mass_component::total_velocity, observed::ObservedPoint,
+};
+
+const MAX_ITERATIONS: usize = 2000;
+const MAX_RESTARTS: usize = 10;
+const TOLERANCE: f64 = 1.0e-10;
+
+#[derive(Clone, Debug)]
+pub struct FitResult {
+    pub model: GalaxyModel,
+    // Root mean square of the (error weighted) residuals
+    pub rms: f64,
+    pub iterations: usize,
+}
+
+// Root mean square of the residuals between model and observation
+pub fn residual_rms(observed: &[ObservedPoint], model: &GalaxyModel) -> f64 {
+    let components = model.components();
+
+    let sum: f64 = observed
+        .iter()
+        .map(|point| {
+            let velocity = total_velocity(&components, point.radius);
+            ((point.velocity - velocity) / point.error.unwrap_or(1.0)).powi(2)
+        })
+        .sum();
+
+    (sum / observed.len() as f64).sqrt()
+}
+
+// Fit the parameters with the given keys, starting from `initial`.
+// The search runs in log space, so all parameters stay positive and within their limits.
+pub fn fit_rotation_curve(observed: &[ObservedPoint], initial: &GalaxyModel, free: &[String]) -> FitResult {
+    let parameters: Vec<_> = initial
+        .parameters()
+        .into_iter()
+        .filter(|parameter| free.contains(&parameter.key))
+        .collect();
+
+    // Build full model from the free coordinates
+    let expand = |coords: &[f64]| -> GalaxyModel {
+        let mut model = initial.clone();
+        for (coord, parameter) in coords.iter().zip(&parameters) {
+            model.set(&parameter.key, 10.0_f64.powf(*coord).clamp(parameter.min, parameter.max));
+        }
+        model
+    };
+
+    // Penalise leaving the limits, so the simplex does not get lost on a flat plateau
+    let penalty = |coords: &[f64]| -> f64 {
+        coords
+            .iter()
+            .zip(&parameters)
+            .map(|(coord, parameter)| {
+                (parameter.min.log10() - coord).max(0.0) + (coord - parameter.max.log10()).max(0.0)
+            })
+            .sum()
+    };
+
+    let objective = |coords: &[f64]| residual_rms(observed, &expand(coords)) * (1.0 + penalty(coords));
+
+    let start: Vec<f64> = parameters
+        .iter()
+        .map(|parameter| {
+            initial
+                .get(&parameter.key)
+                .unwrap_or(parameter.min)
+                .clamp(parameter.min, parameter.max)
+                .log10()
+        })
+        .collect();
+
+    // Restart from the best point until the simplex stops improving
+    let mut best = start;
+    let mut best_value = objective(&best);
+    let mut iterations = 0;
+    for _ in 0..MAX_RESTARTS {
+        let (point, used) = nelder_mead(&objective, &best, 0.3);
+        let value = objective(&point);
+        iterations += used;
+
+        let improved = best_value - value > TOLERANCE * (best_value.abs() + TOLERANCE);
+        if value < best_value {
+            best = point;
+            best_value = value;
+        }
+        if !improved {
+            break;
+        }
+    }
+    let model = expand(&best);
+
+    FitResult {
+        rms: residual_rms(observed, &model),
+        model,
+        iterations,
+    }
+}
+
+// Downhill simplex minimisation, returns the best point and the number of iterations
+fn nelder_mead(objective: &impl Fn(&[f64]) -> f64, start: &[f64], step: f64) -> (Vec<f64>, usize) {
+    let n = start.len();
+    if n == 0 {
+        return (Vec::new(), 0);
+    }
+
+    // Initial simplex around the start point
+    let mut simplex: Vec<Vec<f64>> = vec![start.to_vec()];
+    for i in 0..n {
+        let mut vertex = start.to_vec();
+        vertex[i] += step;
+        simplex.push(vertex);
+    }
+    let mut values: Vec<f64> = simplex.iter().map(|vertex| objective(vertex)).collect();
+
+    let combine = |a: &[f64], b: &[f64], t: f64| -> Vec<f64> {
+        a.iter().zip(b).map(|(a, b)| a + t * (b - a)).collect()
+    };
+
+    for iteration in 0..MAX_ITERATIONS {
+        // Sort vertices from best to worst
+        let mut order: Vec<usize> = (0..=n).collect();
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+        simplex = order.iter().map(|&i| simplex[i].clone()).collect();
+        values = order.iter().map(|&i| values[i]).collect();
+
+        if (values[n] - values[0]).abs() <= TOLERANCE * (values[0].abs() + TOLERANCE) {
+            return (simplex.swap_remove(0), iteration);
+        }
+
+        // Centroid of all but the worst vertex
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|vertex| vertex[j]).sum::<f64>() / n as f64)
+            .collect();
+
+        let reflected = combine(&centroid, &simplex[n], -1.0);
+        let reflected_value = objective(&reflected);
+
+        if reflected_value < values[0] {
+            let expanded = combine(&centroid, &simplex[n], -2.0);
+            let expanded_value = objective(&expanded);
+            if expanded_value < reflected_value {
+                simplex[n] = expanded;
+                values[n] = expanded_value;
+            } else {
+                simplex[n] = reflected;
+                values[n] = reflected_value;
+            }
+        } else if reflected_value < values[n - 1] {
+            simplex[n] = reflected;
+            values[n] = reflected_value;
+        } else {
+            let contracted = combine(&centroid, &simplex[n], 0.5);
+            let contracted_value = objective(&contracted);
+            if contracted_value < values[n] {
+                simplex[n] = contracted;
+                values[n] = contracted_value;
+            } else {
+                // Shrink towards the best vertex
+                for i in 1..=n {
+                    simplex[i] = combine(&simplex[0], &simplex[i], 0.5);
+                    values[i] = objective(&simplex[i]);
+                }
+            }
+        }
+    }
+
+    let best = (0..=n)
+        .min_by(|&a, &b| values[a].total_cmp(&values[b]))
+        .unwrap_or(0);
+    (simplex.swap_remove(best), MAX_ITERATIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Rotation curve of a known model, without errors
+    fn synthetic_curve(model: &GalaxyModel) -> Vec<ObservedPoint> {
+        let components = model.components();
+        (1..=30)
+            .map(|i| {
+                let radius = i as f64;
+                ObservedPoint {
+                    radius,
+                    velocity: total_velocity(&components, radius),
+                    error: None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recovers_the_densities() {
+        let mut truth = GalaxyModel::default();
+        truth.set("disk.density", 0.8);
+        truth.set("halo.density", 5.0e-22);
+        let observed = synthetic_curve(&truth);
+
+        let mut initial = truth.clone();
+        initial.set("disk.density", 0.3);
+        initial.set("halo.density", 1.5e-21);
+        let free = ["disk.density".to_string(), "halo.density".to_string()];
+        let result = fit_rotation_curve(&observed, &initial, &free);
+
+        for key in free {
+            let ratio = result.model.get(&key).unwrap() / truth.get(&key).unwrap();
+            assert!((ratio - 1.0).abs() < 1e-3, "{}: {:?}", key, result);
+        }
+        assert!(result.rms < 1e-2, "{:?}", result);
+    }
+
+    #[test]
+    fn fixed_parameters_are_kept() {
+        let observed = synthetic_curve(&GalaxyModel::default());
+        let mut initial = GalaxyModel::default();
+        initial.set("disk.scale", 3.0);
+        let result = fit_rotation_curve(&observed, &initial, &[]);
+
+        assert_eq!(result.model, initial);
+        assert_eq!(result.rms, residual_rms(&observed, &initial));
+    }
+}
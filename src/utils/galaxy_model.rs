@@ -0,0 +1,798 @@
+// State of the modelled galaxy, shared by all inputs and charts
+use crate::utils::{
+    calculate_density::{density_disk, density_halo, factor_kpc_scaled},
+    calculate_mass::{dynamical_mass, halo_factor, mass_halo},
+    calculate_potential::ISO_REFERENCE_RADIUS,
+    cosmology::{concentration_from_mass, critical_density_at, r200, OVERDENSITY},
+    expression::Formula,
+    mass_component::{
+        ContractedHalo, ExponentialDisk, IsothermalHalo, MassComponent, NfwHalo, TruncatedHalo, CIRCULAR_ORBITS,
+        CUTOFF_DECAY, GNEDIN_ORBITS,
+    },
+    numerical_profile::{SphericalProfile, ThinDiskProfile},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, f64::consts::PI};
+
+// Variables of a custom formula which are given by the model itself
+const BOUND_VARIABLES: [&str; 3] = ["r", "rho0", "a"];
+
+// Range (min, max, step) of the additional parameters of a custom formula
+const CUSTOM_PARAMETER_RANGE: (f64, f64, f64) = (0.01, 10.0, 0.01);
+
+// Range (min, max, step) of the axis ratio of the halo, from flattened to elongated
+const AXIS_RATIO_RANGE: (f64, f64, f64) = (0.1, 2.0, 0.01);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaloProfile {
+    // Density function according to an isothermal gas sphere
+    Isothermal,
+    // Density function according to Navarro, Frenk, and White
+    Nfw,
+}
+
+// Density of the halo beyond r₂₀₀, where it would otherwise hold ever more mass
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaloTruncation {
+    // Extends without limit
+    #[default]
+    None,
+    // No mass beyond r₂₀₀
+    Virial,
+    // Exponentially decreasing beyond r₂₀₀
+    Exponential,
+}
+
+// Response of the halo to the disk forming inside it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaloContraction {
+    // The halo as given
+    #[default]
+    None,
+    // Adiabatic contraction after Blumenthal et al. 1986, circular orbits
+    Blumenthal,
+    // Adiabatic contraction after Gnedin et al. 2004, orbit-averaged radii
+    Gnedin,
+}
+
+// Radius up to which the total mass of a truncated halo is integrated, in truncation radii;
+// the exponential cutoff leaves less than 10^-8 of the density there
+const TOTAL_MASS_RADIUS: f64 = 1.0 + 20.0 * CUTOFF_DECAY;
+
+// Density function typed in by the user, replaces the built-in one
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CustomDensity {
+    // Formula in terms of r, rho0 (initial density), a (scale length) and the parameters
+    pub formula: String,
+    // Values of the additional parameters used in the formula
+    pub parameters: BTreeMap<String, f64>,
+}
+
+impl CustomDensity {
+    // Density as function of the radius, None if the formula is invalid
+    fn density_function(
+        &self,
+        density: f64,
+        scale: f64,
+    ) -> Option<impl Fn(f64) -> f64 + Send + Sync + 'static> {
+        let formula = Formula::parse(&self.formula).ok()?;
+
+        let values: Vec<f64> = formula
+            .variables()
+            .iter()
+            .map(|name| match name.as_str() {
+                "rho0" => density,
+                "a" => scale,
+                _ => self.parameters.get(name).copied().unwrap_or(f64::NAN),
+            })
+            .collect();
+        let radius_index = formula.variables().iter().position(|name| name == "r");
+
+        Some(move |r: f64| {
+            formula.evaluate(|i| if Some(i) == radius_index { r } else { values[i] })
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DiskModel {
+    // Initial surface density in kg/m²
+    pub density: f64,
+    // Scale length in kpc
+    pub scale: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom: Option<CustomDensity>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HaloModel {
+    // Initial density in kg/m³
+    pub density: f64,
+    // Scale length in kpc
+    pub scale: f64,
+    pub profile: HaloProfile,
+    // Axis ratio q of the density ellipsoids: 1 spherical, below 1 oblate, above 1 prolate
+    #[serde(default = "spherical_axis_ratio")]
+    pub axis_ratio: f64,
+    #[serde(default)]
+    pub truncation: HaloTruncation,
+    #[serde(default)]
+    pub contraction: HaloContraction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom: Option<CustomDensity>,
+    // Set if the NFW halo is given by its virial mass and concentration, density and scale follow from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virial: Option<VirialHalo>,
+}
+
+// Limits of the redshift of the virial values
+pub const REDSHIFT_LIMITS: (f64, f64) = (0.0, 10.0);
+
+fn spherical_axis_ratio() -> f64 {
+    1.0
+}
+
+// NFW halo described by M₂₀₀ and the concentration c = r₂₀₀ / a, as quoted in the literature
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VirialHalo {
+    // M₂₀₀ in 10^10 M☉
+    pub mass: f64,
+    pub concentration: f64,
+    // Take the concentration from the c-M relation instead
+    #[serde(default)]
+    pub concentration_relation: bool,
+    // Redshift of the critical density
+    #[serde(default)]
+    pub redshift: f64,
+}
+
+impl VirialHalo {
+    // Virial values of an NFW halo with initial density (kg/m³) and scale length (kpc) today,
+    // None if its r₂₀₀ can not be found
+    pub fn from_nfw(density: f64, scale: f64) -> Option<Self> {
+        let mass = |r: f64| mass_halo(r, density, scale, false) * halo_factor();
+        let radius = r200(mass, 0.0)?;
+        Some(Self {
+            mass: mass(radius),
+            concentration: radius / scale,
+            concentration_relation: false,
+            redshift: 0.0,
+        })
+    }
+
+    pub fn concentration(&self) -> f64 {
+        if self.concentration_relation {
+            concentration_from_mass(self.mass, self.redshift)
+        } else {
+            self.concentration
+        }
+    }
+
+    // r₂₀₀ in kpc
+    pub fn radius(&self) -> f64 {
+        (3.0 * self.mass / (4.0 * PI * OVERDENSITY * critical_density_at(self.redshift))).cbrt()
+    }
+
+    // Circular velocity at r₂₀₀ in km/s
+    pub fn velocity(&self) -> f64 {
+        (self.mass / dynamical_mass(self.radius(), 1.0)).sqrt()
+    }
+
+    // Initial density (kg/m³) and scale length (kpc) of the NFW profile
+    pub fn nfw_parameters(&self) -> (f64, f64) {
+        let concentration = self.concentration();
+        let scale = self.radius() / concentration;
+        let shape = (1.0 + concentration).ln() - concentration / (1.0 + concentration);
+        let density = self.mass / (4.0 * PI * scale.powi(3) * shape) / halo_factor();
+        (density, scale)
+    }
+}
+
+// Cosmic baryon fraction Ω_b / Ω_m (Planck 2018)
+pub const COSMIC_BARYON_FRACTION: f64 = 0.157;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GalaxyModel {
+    pub disk: DiskModel,
+    pub halo: HaloModel,
+}
+
+// A value of the model which can be changed by the user
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelParameter {
+    // Key for `GalaxyModel::get` and `GalaxyModel::set`
+    pub key: String,
+    // Component the parameter belongs to
+    pub group: String,
+    // Label, also used as key for the translation
+    pub label: String,
+    pub unit: String,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+    // Show value in scientific notation
+    pub exponential: bool,
+}
+
+impl ModelParameter {
+    fn new(key: &str, group: &str, label: &str, unit: &str, (min, max, step): (f64, f64, f64)) -> Self {
+        Self {
+            key: key.to_string(),
+            group: group.to_string(),
+            label: label.to_string(),
+            unit: unit.to_string(),
+            min,
+            max,
+            step,
+            exponential: false,
+        }
+    }
+
+    fn exponential(mut self) -> Self {
+        self.exponential = true;
+        self
+    }
+}
+
+impl Default for GalaxyModel {
+    fn default() -> Self {
+        Self {
+            disk: DiskModel {
+                density: 1.01,
+                scale: 4.5,
+                custom: None,
+            },
+            halo: HaloModel {
+                density: 1.52e-21,
+                scale: 15.91,
+                profile: HaloProfile::Isothermal,
+                axis_ratio: spherical_axis_ratio(),
+                truncation: HaloTruncation::None,
+                contraction: HaloContraction::None,
+                custom: None,
+                virial: None,
+            },
+        }
+    }
+}
+
+impl GalaxyModel {
+    // Mass components described by the model, custom density functions are integrated numerically
+    pub fn components(&self) -> Vec<Box<dyn MassComponent>> {
+        self.disk_and_halo().into()
+    }
+
+    // The baryonic and the dark component
+    pub fn disk_and_halo(&self) -> [Box<dyn MassComponent>; 2] {
+        self.contract(|| self.initial_disk_and_halo())
+    }
+
+    // Disk and halo before the contraction of the halo
+    fn initial_disk_and_halo(&self) -> [Box<dyn MassComponent>; 2] {
+        let [custom_disk, custom_halo] = self.custom_components();
+
+        let disk = custom_disk.unwrap_or_else(|| {
+            Box::new(ExponentialDisk {
+                density: self.disk.density,
+                scale: self.disk.scale,
+            })
+        });
+
+        let halo = custom_halo.unwrap_or_else(|| match self.halo.profile {
+            HaloProfile::Isothermal => Box::new(IsothermalHalo {
+                density: self.halo.density,
+                scale: self.halo.scale,
+                axis_ratio: self.halo.axis_ratio,
+            }),
+            HaloProfile::Nfw => Box::new(NfwHalo {
+                density: self.halo.density,
+                scale: self.halo.scale,
+                axis_ratio: self.halo.axis_ratio,
+            }),
+        });
+
+        [disk, self.truncate(halo)]
+    }
+
+    // r₂₀₀ of the halo alone, None if it can not be found. The mean density within an ellipsoid
+    // is that of the spherical halo, so the mass is compared as if the halo was spherical.
+    fn virial_radius(&self, halo: &dyn MassComponent) -> Option<f64> {
+        self.virial()
+            .map(|virial| virial.radius())
+            .or_else(|| r200(|r| halo.mass(r) / halo.axis_ratio(), self.redshift()))
+    }
+
+    // Radius at which the halo is cut off, None if it is not truncated
+    fn truncation_radius(&self, halo: &dyn MassComponent) -> Option<f64> {
+        if self.halo.truncation == HaloTruncation::None {
+            return None;
+        }
+        self.virial_radius(halo)
+    }
+
+    fn truncate(&self, halo: Box<dyn MassComponent>) -> Box<dyn MassComponent> {
+        match self.truncation_radius(halo.as_ref()) {
+            Some(radius) => Box::new(TruncatedHalo {
+                halo,
+                radius,
+                smooth: self.halo.truncation == HaloTruncation::Exponential,
+            }),
+            None => halo,
+        }
+    }
+
+    // Contract the halo of the components in response to their disk, if it is switched on and the halo is spherical
+    fn contract(&self, components: impl Fn() -> [Box<dyn MassComponent>; 2]) -> [Box<dyn MassComponent>; 2] {
+        let [disk, halo] = components();
+        let orbits = match self.halo.contraction {
+            HaloContraction::None => return [disk, halo],
+            HaloContraction::Blumenthal => CIRCULAR_ORBITS,
+            HaloContraction::Gnedin => GNEDIN_ORBITS,
+        };
+        if halo.axis_ratio() != 1.0 {
+            return [disk, halo];
+        }
+
+        // The contracted halo holds a disk of its own, r₂₀₀ is only searched once it is used
+        let [contracting_disk, _] = components();
+        let virial_radius = self.virial().map(|virial| virial.radius());
+        [
+            disk,
+            Box::new(ContractedHalo::new(halo, contracting_disk, orbits, virial_radius)),
+        ]
+    }
+
+    // Mass of the whole halo in 10^10 M☉, None if it is not truncated
+    pub fn total_halo_mass(&self) -> Option<f64> {
+        // The contraction keeps the mass of the halo
+        let [_, halo] = self.initial_disk_and_halo();
+        // Within r₂₀₀ the truncated halo is the same, so its r₂₀₀ is the truncation radius
+        let radius = self.truncation_radius(halo.as_ref())?;
+        Some(halo.mass(radius * TOTAL_MASS_RADIUS))
+    }
+
+    // Share of the halo in the mass enclosed within a radius, NaN at the center
+    pub fn dark_matter_fraction(&self) -> impl Fn(f64) -> f64 {
+        let [disk, halo] = self.disk_and_halo();
+        move |radius| {
+            let (baryonic, dark) = (disk.mass(radius), halo.mass(radius));
+            dark / (baryonic + dark)
+        }
+    }
+
+    // Same components, but integrated numerically from their density functions
+    pub fn numerical_components(&self) -> Vec<Box<dyn MassComponent>> {
+        self.contract(|| self.initial_numerical_components()).into()
+    }
+
+    fn initial_numerical_components(&self) -> [Box<dyn MassComponent>; 2] {
+        let (density, scale) = (self.disk.density, self.disk.scale);
+        let disk = ThinDiskProfile {
+            name: "Disk",
+            surface_density: move |r: f64| density_disk(r, density, scale),
+        };
+
+        let (density, scale) = (self.halo.density, self.halo.scale);
+        let iso_nfw = self.halo.profile == HaloProfile::Isothermal;
+        let halo = SphericalProfile {
+            name: "Halo",
+            density: move |r: f64| density_halo(r, density, scale, iso_nfw) / factor_kpc_scaled(),
+            reference: if iso_nfw { ISO_REFERENCE_RADIUS } else { f64::INFINITY },
+        };
+
+        let [custom_disk, custom_halo] = self.custom_components();
+        [
+            custom_disk.unwrap_or(Box::new(disk)),
+            self.truncate(custom_halo.unwrap_or(Box::new(halo))),
+        ]
+    }
+
+    // Disk and halo built from the custom density functions, if there are any
+    fn custom_components(&self) -> [Option<Box<dyn MassComponent>>; 2] {
+        let disk = self.disk.custom.as_ref().and_then(|custom| {
+            let surface_density = custom.density_function(self.disk.density, self.disk.scale)?;
+            Some(Box::new(ThinDiskProfile {
+                name: "Disk",
+                surface_density,
+            }) as Box<dyn MassComponent>)
+        });
+
+        // Whether the mass converges is unknown, so the potential is zeroed like the isothermal one
+        let halo = self.halo.custom.as_ref().and_then(|custom| {
+            let density = custom.density_function(self.halo.density, self.halo.scale)?;
+            Some(Box::new(SphericalProfile {
+                name: "Halo",
+                density,
+                reference: ISO_REFERENCE_RADIUS,
+            }) as Box<dyn MassComponent>)
+        });
+
+        [disk, halo]
+    }
+
+    pub fn custom(&self, group: &str) -> Option<&CustomDensity> {
+        match group {
+            "disk" => self.disk.custom.as_ref(),
+            "halo" => self.halo.custom.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn custom_mut(&mut self, group: &str) -> Option<&mut Option<CustomDensity>> {
+        match group {
+            "disk" => Some(&mut self.disk.custom),
+            "halo" => Some(&mut self.halo.custom),
+            _ => None,
+        }
+    }
+
+    // Replace the density function of "disk" or "halo" with a formula, an empty one restores the built-in.
+    // Parameters which were already used keep their value.
+    pub fn set_formula(&mut self, group: &str, formula: &str) -> Result<(), String> {
+        let custom = self
+            .custom_mut(group)
+            .ok_or_else(|| format!("Unknown component {}", group))?;
+
+        if formula.trim().is_empty() {
+            *custom = None;
+            return Ok(());
+        }
+
+        let parsed = Formula::parse(formula)?;
+        let previous = custom.take().map(|custom| custom.parameters).unwrap_or_default();
+        let parameters = parsed
+            .variables()
+            .iter()
+            .filter(|name| !BOUND_VARIABLES.contains(&name.as_str()))
+            .map(|name| (name.clone(), previous.get(name).copied().unwrap_or(1.0)))
+            .collect();
+
+        *custom = Some(CustomDensity {
+            formula: formula.trim().to_string(),
+            parameters,
+        });
+        Ok(())
+    }
+
+    // All parameters the user can change, in the order of the inputs
+    pub fn parameters(&self) -> Vec<ModelParameter> {
+        let custom_parameters = |group: &str| -> Vec<ModelParameter> {
+            self.custom(group)
+                .map(|custom| {
+                    custom
+                        .parameters
+                        .keys()
+                        .map(|name| {
+                            let key = format!("{}.custom.{}", group, name);
+                            ModelParameter::new(&key, group, name, "", CUSTOM_PARAMETER_RANGE)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut parameters = vec![
+            ModelParameter::new(
+                "disk.density",
+                "disk",
+                "Initial surface density of the disk",
+                "kg/m²",
+                (0.01, 2.0, 0.01),
+            ),
+            ModelParameter::new(
+                "disk.scale",
+                "disk",
+                "Scale length of the disk",
+                "kpc",
+                (0.2, 8.8, 0.01),
+            ),
+        ];
+        parameters.extend(custom_parameters("disk"));
+        match self.virial() {
+            Some(virial) => {
+                parameters.push(ModelParameter::new(
+                    "halo.virial.mass",
+                    "halo",
+                    "Virial mass of the halo (M₂₀₀)",
+                    "10^10 M☉",
+                    (1.0, 2000.0, 1.0),
+                ));
+                if !virial.concentration_relation {
+                    parameters.push(ModelParameter::new(
+                        "halo.virial.concentration",
+                        "halo",
+                        "Concentration of the halo",
+                        "",
+                        (1.0, 40.0, 0.1),
+                    ));
+                }
+            }
+            None => parameters.extend([
+                ModelParameter::new(
+                    "halo.density",
+                    "halo",
+                    "Initial density of the halo",
+                    "kg/m³",
+                    (1.0e-23, 3.03e-21, 1.0e-23),
+                )
+                .exponential(),
+                ModelParameter::new(
+                    "halo.scale",
+                    "halo",
+                    "Scale length of the halo",
+                    "kpc",
+                    (0.01, 31.8, 0.01),
+                ),
+            ]),
+        }
+        // The shape only applies to the built-in density functions
+        if self.halo.custom.is_none() {
+            parameters.push(ModelParameter::new(
+                "halo.axis_ratio",
+                "halo",
+                "Axis ratio of the halo",
+                "",
+                AXIS_RATIO_RANGE,
+            ));
+        }
+        parameters.extend(custom_parameters("halo"));
+        parameters
+    }
+
+    // Virial description of the halo, only used for NFW halos
+    pub fn virial(&self) -> Option<&VirialHalo> {
+        self.halo.virial.as_ref().filter(|_| self.halo.profile == HaloProfile::Nfw)
+    }
+
+    // Redshift of the critical density which defines r₂₀₀, only set for virial halos
+    pub fn redshift(&self) -> f64 {
+        self.virial().map(|virial| virial.redshift).unwrap_or_default()
+    }
+
+    // Describe the NFW halo by its virial values, starting from the current density and scale length,
+    // or go back to density and scale length. Fails if r₂₀₀ of the current halo can not be found.
+    pub fn set_virial(&mut self, enabled: bool) -> Result<(), String> {
+        self.halo.virial = None;
+        if enabled {
+            self.halo.virial = Some(
+                VirialHalo::from_nfw(self.halo.density, self.halo.scale)
+                    .ok_or("r₂₀₀ of the halo can not be found")?,
+            );
+        }
+        Ok(())
+    }
+
+    // Change the virial values, density and scale length follow
+    pub fn update_virial(&mut self, update: impl FnOnce(&mut VirialHalo)) {
+        if let Some(virial) = self.halo.virial.as_mut() {
+            update(virial);
+            (self.halo.density, self.halo.scale) = virial.nfw_parameters();
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<f64> {
+        match key {
+            "disk.density" => Some(self.disk.density),
+            "disk.scale" => Some(self.disk.scale),
+            "halo.density" => Some(self.halo.density),
+            "halo.scale" => Some(self.halo.scale),
+            "halo.axis_ratio" => Some(self.halo.axis_ratio),
+            "halo.virial.mass" => self.virial().map(|virial| virial.mass),
+            "halo.virial.concentration" => self.virial().map(|virial| virial.concentration),
+            _ => {
+                let (group, name) = key.split_once(".custom.")?;
+                self.custom(group)?.parameters.get(name).copied()
+            }
+        }
+    }
+
+    // Set a parameter, returns false for unknown keys and for density and scale length of the halo
+    // while they follow from its virial values
+    pub fn set(&mut self, key: &str, value: f64) -> bool {
+        if self.virial().is_some() && matches!(key, "halo.density" | "halo.scale") {
+            return false;
+        }
+        if let Some(name) = key.strip_prefix("halo.virial.") {
+            if self.virial().is_none() {
+                return false;
+            }
+            match name {
+                "mass" => self.update_virial(|virial| virial.mass = value),
+                "concentration" => self.update_virial(|virial| virial.concentration = value),
+                _ => return false,
+            }
+            return true;
+        }
+
+        let target = match key {
+            "disk.density" => &mut self.disk.density,
+            "disk.scale" => &mut self.disk.scale,
+            "halo.density" => &mut self.halo.density,
+            "halo.scale" => &mut self.halo.scale,
+            "halo.axis_ratio" => &mut self.halo.axis_ratio,
+            _ => {
+                let Some((group, name)) = key.split_once(".custom.") else {
+                    return false;
+                };
+                match self
+                    .custom_mut(group)
+                    .and_then(|custom| custom.as_mut())
+                    .and_then(|custom| custom.parameters.get_mut(name))
+                {
+                    Some(target) => target,
+                    None => return false,
+                }
+            }
+        };
+        *target = value;
+        true
+    }
+
+    // Keys of all values which differ from the other model
+    pub fn diff(&self, other: &GalaxyModel) -> Vec<String> {
+        let mut changed: Vec<String> = self
+            .parameters()
+            .into_iter()
+            .filter(|parameter| self.get(&parameter.key) != other.get(&parameter.key))
+            .map(|parameter| parameter.key)
+            .collect();
+
+        if self.halo.profile != other.halo.profile {
+            changed.push("halo.profile".to_string());
+        }
+        if self.halo.truncation != other.halo.truncation {
+            changed.push("halo.truncation".to_string());
+        }
+        if self.halo.contraction != other.halo.contraction {
+            changed.push("halo.contraction".to_string());
+        }
+        let virial_settings = |model: &GalaxyModel| {
+            model.virial().map(|virial| (virial.concentration_relation, virial.redshift))
+        };
+        if virial_settings(self) != virial_settings(other) {
+            changed.push("halo.virial".to_string());
+        }
+        for group in ["disk", "halo"] {
+            let formula = |model: &GalaxyModel| model.custom(group).map(|custom| custom.formula.clone());
+            if formula(self) != formula(other) {
+                changed.push(format!("{}.formula", group));
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_every_parameter() {
+        let mut model = GalaxyModel::default();
+        for parameter in model.parameters() {
+            let value = (parameter.min + parameter.max) / 2.0;
+            assert!(model.set(&parameter.key, value), "{}", parameter.key);
+            assert_eq!(model.get(&parameter.key), Some(value), "{}", parameter.key);
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let mut model = GalaxyModel::default();
+        assert!(!model.set("disk.mass", 1.0));
+        assert_eq!(model.get("disk.mass"), None);
+        assert_eq!(model, GalaxyModel::default());
+    }
+
+    #[test]
+    fn diff_lists_the_changed_values() {
+        let model = GalaxyModel::default();
+        assert!(model.diff(&model).is_empty());
+
+        let mut other = model.clone();
+        other.set("disk.scale", 2.0);
+        other.halo.profile = HaloProfile::Nfw;
+        assert_eq!(model.diff(&other), ["disk.scale", "halo.profile"]);
+
+        other.halo.truncation = HaloTruncation::Virial;
+        assert_eq!(model.diff(&other), ["disk.scale", "halo.profile", "halo.truncation"]);
+
+        other.halo.contraction = HaloContraction::Gnedin;
+        assert_eq!(
+            model.diff(&other),
+            ["disk.scale", "halo.profile", "halo.truncation", "halo.contraction"]
+        );
+    }
+
+    #[test]
+    fn virial_values_round_trip() {
+        let mut model = GalaxyModel::default();
+        model.halo.profile = HaloProfile::Nfw;
+        model.halo.density = 5.0e-21;
+        let (density, scale) = (model.halo.density, model.halo.scale);
+        assert_eq!(model.get("halo.virial.mass"), None);
+        assert!(!model.set("halo.virial.mass", 100.0));
+
+        // Switching to the virial values keeps the profile
+        let nfw = model.clone();
+        model.set_virial(true).unwrap();
+        assert_eq!(nfw.diff(&model), ["halo.virial"]);
+        let virial = *model.virial().unwrap();
+        let (virial_density, virial_scale) = virial.nfw_parameters();
+        assert!((virial_density / density - 1.0).abs() < 1e-9);
+        assert!((virial_scale / scale - 1.0).abs() < 1e-9);
+        assert!((virial.radius() / (virial.concentration * scale) - 1.0).abs() < 1e-9);
+
+        // Density and scale follow the virial values
+        let switched = model.clone();
+        assert!(model.set("halo.virial.mass", 2.0 * virial.mass));
+        assert_eq!(switched.diff(&model), ["halo.virial.mass"]);
+        assert_eq!(model.get("halo.virial.mass"), Some(2.0 * virial.mass));
+        assert_eq!(model.get("halo.virial.concentration"), Some(virial.concentration));
+        assert!((model.halo.scale / (scale * 2.0_f64.cbrt()) - 1.0).abs() < 1e-9);
+
+        // And stay when switching back
+        let changed = model.halo.clone();
+        model.set_virial(false).unwrap();
+        assert_eq!(model.virial(), None);
+        assert_eq!((model.halo.density, model.halo.scale), (changed.density, changed.scale));
+    }
+
+    #[test]
+    fn truncated_mass_is_constant_beyond_r200() {
+        let mut model = GalaxyModel::default();
+        assert_eq!(model.total_halo_mass(), None);
+
+        model.halo.truncation = HaloTruncation::Virial;
+        let halo = &model.components()[1];
+        let radius = r200(|r| halo.mass(r), 0.0).unwrap();
+        let total = model.total_halo_mass().unwrap();
+        assert_eq!(halo.mass(radius), total);
+        for r in [1.01 * radius, 2.0 * radius, 10.0 * radius] {
+            assert_eq!(halo.mass(r), total);
+            assert_eq!(halo.density(r), 0.0);
+        }
+
+        // The exponential cutoff still adds mass, up to the total
+        model.halo.truncation = HaloTruncation::Exponential;
+        let halo = &model.components()[1];
+        let total = model.total_halo_mass().unwrap();
+        assert!(halo.mass(1.1 * radius) > halo.mass(radius));
+        assert!((halo.mass(4.0 * radius) / total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn contraction_pulls_the_halo_inwards() {
+        let mut model = GalaxyModel::default();
+        model.halo.profile = HaloProfile::Nfw;
+        let initial = model.components();
+
+        for contraction in [HaloContraction::Blumenthal, HaloContraction::Gnedin] {
+            model.halo.contraction = contraction;
+            let contracted = model.components();
+            for r in [0.5, 2.0, 5.0, 10.0, 30.0] {
+                assert!(
+                    contracted[1].mass(r) >= initial[1].mass(r),
+                    "{contraction:?} at {r} kpc: {} < {}",
+                    contracted[1].mass(r),
+                    initial[1].mass(r)
+                );
+            }
+            // The disk stays as it is
+            assert_eq!(contracted[0].mass(10.0), initial[0].mass(10.0));
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut model = GalaxyModel::default();
+        model.set("halo.scale", 7.5);
+        model.halo.profile = HaloProfile::Nfw;
+        let json = serde_json::to_string(&model).unwrap();
+        assert_eq!(serde_json::from_str::<GalaxyModel>(&json).unwrap(), model);
+    }
+}
@@ -0,0 +1,69 @@
+// Dark matter implied by an observed rotation curve
+use crate::utils::{
+    calculate_density::factor_kpc_scaled,
+    calculate_mass::{dynamical_mass, halo_factor},
+    mass_component::MassComponent,
+    observed::ObservedPoint,
+};
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InferredPoint {
+    // kpc
+    pub radius: f64,
+    // Enclosed masses in 10^10 M☉
+    pub dynamical_mass: f64,
+    pub dark_mass: f64,
+    // Spherical density of the dark matter, in the units of the halo density
+    pub dark_density: f64,
+}
+
+// Enclosed mass from v²r/G at each observed radius, minus the modelled disk.
+// The density follows from the change of the dark mass between neighbouring radii.
+pub fn infer_dark_matter(observed: &[ObservedPoint], disk: &dyn MassComponent) -> Vec<InferredPoint> {
+    let masses: Vec<(f64, f64, f64)> = observed
+        .iter()
+        .filter(|point| point.radius > 0.0)
+        .map(|point| {
+            let dynamical = dynamical_mass(point.radius, point.velocity);
+            (point.radius, dynamical, dynamical - disk.mass(point.radius))
+        })
+        .collect();
+
+    (0..masses.len())
+        .map(|i| {
+            let (radius, dynamical_mass, dark_mass) = masses[i];
+
+            // Central differences, one-sided at the ends
+            let (r0, _, m0) = masses[i.saturating_sub(1)];
+            let (r1, _, m1) = masses[(i + 1).min(masses.len() - 1)];
+            let dark_density = if r1 > r0 {
+                let density = (m1 - m0) / (r1 - r0) / (4.0 * PI * radius.powi(2));
+                // 10^10 M☉/kpc³ to the units of `density_halo`
+                density / halo_factor() * factor_kpc_scaled()
+            } else {
+                f64::NAN
+            };
+
+            InferredPoint {
+                radius,
+                dynamical_mass,
+                dark_mass,
+                dark_density,
+            }
+        })
+        .collect()
+}
+
+// Linear interpolation of a value of the inferred points, NaN outside of their radii
+pub fn interpolate(points: &[InferredPoint], radius: f64, value: fn(&InferredPoint) -> f64) -> f64 {
+    let next_i = points.partition_point(|point| point.radius < radius);
+    match (next_i.checked_sub(1).and_then(|i| points.get(i)), points.get(next_i)) {
+        (_, Some(next)) if next.radius == radius => value(next),
+        (Some(prev), Some(next)) => {
+            let t = (radius - prev.radius) / (next.radius - prev.radius);
+            value(prev) + t * (value(next) - value(prev))
+        }
+        _ => f64::NAN,
+    }
+}
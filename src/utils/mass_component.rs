@@ -0,0 +1,564 @@
+// Mass components of a galaxy, e.g. the disk and the halo
+use crate::utils::{
+    calculate_density::{density_disk, density_halo, factor_kpc_scaled},
+    calculate_mass::{disk_factor, dynamical_mass, halo_factor, mass_disk, mass_halo, mass_halo_ellipsoidal, midplane_mass},
+    calculate_potential::{kpc2_to_km2, potential_disk, potential_ellipsoidal_shells, potential_halo, potential_halo_ellipsoidal},
+    calculate_velocity::{velocity_disk, velocity_halo, velocity_halo_ellipsoidal},
+    cosmology::r200,
+    quadrature::{integrate, integrate_to_infinity},
+};
+use scilib::constant::G;
+use std::{f64::consts::PI, sync::OnceLock};
+
+// Decay length of the exponential cutoff of a halo relative to its truncation radius
+pub const CUTOFF_DECAY: f64 = 0.1;
+
+// Orbit-averaged radius r̄ = A₀ · r_vir · (r / r_vir)^w of the Gnedin et al. 2004 prescription as (A₀, w);
+// Blumenthal et al. 1986 assume circular orbits, that is (1, 1)
+pub const GNEDIN_ORBITS: (f64, f64) = (0.85, 0.8);
+pub const CIRCULAR_ORBITS: (f64, f64) = (1.0, 1.0);
+
+// Relative step of the derivative giving the density of a contracted halo
+const DENSITY_STEP: f64 = 1.0e-4;
+// Radii at which the mass and the potential of a contracted halo are stored, in virial radii,
+// and their spacing. Beyond, the disk holds all its mass and the shells are back at their initial radii.
+const CONTRACTION_RANGE: (f64, f64) = (1.0e-4, 2.0);
+const MASS_NODES_PER_DECADE: usize = 50;
+const POTENTIAL_NODES_PER_DECADE: usize = 20;
+
+pub trait MassComponent: Send + Sync {
+    // Name of the component, also used as key for the translation
+    fn name(&self) -> &'static str;
+
+    // Density in the units of the density chart
+    fn density(&self, radius: f64) -> f64;
+
+    // Enclosed mass in 10^10 M☉
+    fn mass(&self, radius: f64) -> f64;
+
+    // Circular velocity in km/s
+    fn velocity(&self, radius: f64) -> f64;
+
+    // Gravitational potential in (km/s)²
+    fn potential(&self, radius: f64) -> f64;
+
+    // Axis ratio q of the density ellipsoids, the mass is that within the ellipsoid of semi-major axis r
+    fn axis_ratio(&self) -> f64 {
+        1.0
+    }
+}
+
+// Disk with exponentially decreasing surface density
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExponentialDisk {
+    pub density: f64,
+    pub scale: f64,
+}
+
+impl MassComponent for ExponentialDisk {
+    fn name(&self) -> &'static str {
+        "Disk"
+    }
+
+    fn density(&self, radius: f64) -> f64 {
+        density_disk(radius, self.density, self.scale)
+    }
+
+    fn mass(&self, radius: f64) -> f64 {
+        mass_disk(radius, self.density, self.scale) * disk_factor()
+    }
+
+    fn velocity(&self, radius: f64) -> f64 {
+        if radius == 0.0 {
+            return 0.0;
+        }
+        velocity_disk(radius, self.density, self.scale)
+    }
+
+    fn potential(&self, radius: f64) -> f64 {
+        potential_disk(radius, self.density, self.scale)
+    }
+}
+
+// Halo with a density function according to an isothermal gas sphere
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IsothermalHalo {
+    pub density: f64,
+    pub scale: f64,
+    // Axis ratio q of the density ellipsoids, 1 for a sphere
+    pub axis_ratio: f64,
+}
+
+impl MassComponent for IsothermalHalo {
+    fn name(&self) -> &'static str {
+        "Halo"
+    }
+
+    fn density(&self, radius: f64) -> f64 {
+        density_halo(radius, self.density, self.scale, true)
+    }
+
+    fn mass(&self, radius: f64) -> f64 {
+        if self.axis_ratio == 1.0 {
+            mass_halo(radius, self.density, self.scale, true) * halo_factor()
+        } else {
+            mass_halo_ellipsoidal(radius, self.density, self.scale, true, self.axis_ratio) * halo_factor()
+        }
+    }
+
+    fn velocity(&self, radius: f64) -> f64 {
+        if radius == 0.0 {
+            return 0.0;
+        }
+        if self.axis_ratio == 1.0 {
+            velocity_halo(radius, self.density, self.scale, true)
+        } else {
+            velocity_halo_ellipsoidal(radius, self.density, self.scale, true, self.axis_ratio)
+        }
+    }
+
+    fn potential(&self, radius: f64) -> f64 {
+        if self.axis_ratio == 1.0 {
+            potential_halo(radius, self.density, self.scale, true)
+        } else {
+            potential_halo_ellipsoidal(radius, self.density, self.scale, true, self.axis_ratio)
+        }
+    }
+
+    fn axis_ratio(&self) -> f64 {
+        self.axis_ratio
+    }
+}
+
+// Halo with a density function according to Navarro, Frenk, and White
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NfwHalo {
+    pub density: f64,
+    pub scale: f64,
+    // Axis ratio q of the density ellipsoids, 1 for a sphere
+    pub axis_ratio: f64,
+}
+
+impl MassComponent for NfwHalo {
+    fn name(&self) -> &'static str {
+        "Halo"
+    }
+
+    fn density(&self, radius: f64) -> f64 {
+        density_halo(radius, self.density, self.scale, false)
+    }
+
+    fn mass(&self, radius: f64) -> f64 {
+        if self.axis_ratio == 1.0 {
+            mass_halo(radius, self.density, self.scale, false) * halo_factor()
+        } else {
+            mass_halo_ellipsoidal(radius, self.density, self.scale, false, self.axis_ratio) * halo_factor()
+        }
+    }
+
+    fn velocity(&self, radius: f64) -> f64 {
+        if radius == 0.0 {
+            return 0.0;
+        }
+        if self.axis_ratio == 1.0 {
+            velocity_halo(radius, self.density, self.scale, false)
+        } else {
+            velocity_halo_ellipsoidal(radius, self.density, self.scale, false, self.axis_ratio)
+        }
+    }
+
+    fn potential(&self, radius: f64) -> f64 {
+        if self.axis_ratio == 1.0 {
+            potential_halo(radius, self.density, self.scale, false)
+        } else {
+            potential_halo_ellipsoidal(radius, self.density, self.scale, false, self.axis_ratio)
+        }
+    }
+
+    fn axis_ratio(&self) -> f64 {
+        self.axis_ratio
+    }
+}
+
+// Halo cut off at a radius, either sharply or with exponentially decreasing density beyond it,
+// so its total mass is finite. The potential is zero at infinity. An ellipsoidal halo is cut off
+// at the ellipsoid with the radius as semi-major axis.
+pub struct TruncatedHalo {
+    pub halo: Box<dyn MassComponent>,
+    // Truncation radius in kpc
+    pub radius: f64,
+    // Exponential cutoff instead of no mass beyond the radius
+    pub smooth: bool,
+}
+
+impl TruncatedHalo {
+    // Density in kg/m³ beyond the truncation radius
+    fn outer_density(&self, radius: f64) -> f64 {
+        if !self.smooth {
+            return 0.0;
+        }
+        let cutoff = (-(radius - self.radius) / (CUTOFF_DECAY * self.radius)).exp();
+        self.halo.density(radius) / factor_kpc_scaled() * cutoff
+    }
+
+    // Enclosed mass in kg/m³ * kpc³ beyond the truncation radius
+    fn outer_mass(&self, radius: f64) -> f64 {
+        let inner = self.halo.mass(self.radius) / halo_factor();
+        if !self.smooth {
+            return inner;
+        }
+        let shells = 4.0 * PI * integrate(|r| self.outer_density(r) * r.powi(2), self.radius, radius);
+        inner + self.halo.axis_ratio() * shells
+    }
+
+    // Density of the halo with the cutoff in kg/m³
+    fn truncated_density(&self, radius: f64) -> f64 {
+        if radius <= self.radius {
+            self.halo.density(radius) / factor_kpc_scaled()
+        } else {
+            self.outer_density(radius)
+        }
+    }
+
+    // Midplane mass in kg/m³ * kpc³ of an ellipsoidal halo beyond the truncation radius
+    fn outer_midplane_mass(&self, radius: f64) -> f64 {
+        let axis_ratio = self.halo.axis_ratio();
+        let density = |r: f64| if r == 0.0 { 0.0 } else { self.truncated_density(r) };
+        let inner = midplane_mass(radius, density, axis_ratio, 0.0, self.radius);
+        inner + midplane_mass(radius, density, axis_ratio, self.radius, radius)
+    }
+
+    // Potential beyond the truncation radius in (km/s)²
+    fn outer_potential(&self, radius: f64) -> f64 {
+        let outside = if self.smooth {
+            4.0 * PI * integrate_to_infinity(|r| self.outer_density(r) * r, radius, CUTOFF_DECAY * self.radius)
+        } else {
+            0.0
+        };
+        -G * (self.outer_mass(radius) / radius + outside) * kpc2_to_km2()
+    }
+}
+
+impl MassComponent for TruncatedHalo {
+    fn name(&self) -> &'static str {
+        self.halo.name()
+    }
+
+    fn density(&self, radius: f64) -> f64 {
+        if radius <= self.radius {
+            self.halo.density(radius)
+        } else {
+            self.outer_density(radius) * factor_kpc_scaled()
+        }
+    }
+
+    fn mass(&self, radius: f64) -> f64 {
+        if radius <= self.radius {
+            self.halo.mass(radius)
+        } else {
+            self.outer_mass(radius) * halo_factor()
+        }
+    }
+
+    // Shells beyond the radius do not act on it, also for ellipsoidal halos
+    fn velocity(&self, radius: f64) -> f64 {
+        if radius <= self.radius {
+            self.halo.velocity(radius)
+        } else if self.halo.axis_ratio() == 1.0 {
+            (self.mass(radius) / dynamical_mass(radius, 1.0)).sqrt()
+        } else {
+            (self.outer_midplane_mass(radius) * halo_factor() / dynamical_mass(radius, 1.0)).sqrt()
+        }
+    }
+
+    // Inside, the potential of the halo is shifted to match the outer one at the radius
+    fn potential(&self, radius: f64) -> f64 {
+        let axis_ratio = self.halo.axis_ratio();
+        if axis_ratio != 1.0 {
+            // Split at the truncation radius, where the density has a kink or a jump
+            let density = |r: f64| if r == 0.0 { 0.0 } else { self.truncated_density(r) };
+            let outer = if self.smooth { f64::INFINITY } else { self.radius };
+            potential_ellipsoidal_shells(radius, density, axis_ratio, 0.0, self.radius)
+                + potential_ellipsoidal_shells(radius, density, axis_ratio, self.radius, outer)
+        } else if radius <= self.radius {
+            self.halo.potential(radius) - self.halo.potential(self.radius) + self.outer_potential(self.radius)
+        } else {
+            self.outer_potential(radius)
+        }
+    }
+
+    fn axis_ratio(&self) -> f64 {
+        self.halo.axis_ratio()
+    }
+}
+
+// Halo contracted adiabatically as the disk formed inside it. Before, the baryons followed the
+// dark matter within the virial radius; shells of dark matter keep r · M(r̄) while they are pulled in.
+// Only spherical halos are contracted.
+pub struct ContractedHalo {
+    halo: Box<dyn MassComponent>,
+    disk: Box<dyn MassComponent>,
+    // Orbit-averaged radius as (A₀, w), see GNEDIN_ORBITS
+    orbits: (f64, f64),
+    // Virial radius r₂₀₀ of the halo in kpc, None to find it when the halo is first used
+    virial_radius: Option<f64>,
+    // Contracted mass at logarithmically spaced radii, None if r₂₀₀ can not be found
+    profile: OnceLock<Option<ContractedProfile>>,
+    // ∫ ΔM(r) / r² dr from each stored radius to the last one, filled when the potential is first needed
+    moved_potential: OnceLock<Vec<(f64, f64)>>,
+}
+
+// ln(M_contracted / M_halo) at the radii ln r₀ + k · step
+struct ContractedProfile {
+    first: f64,
+    step: f64,
+    ratios: Vec<f64>,
+}
+
+impl ContractedProfile {
+    // Radii in kpc covered by the profile
+    fn range(&self) -> (f64, f64) {
+        (self.first.exp(), (self.first + self.step * (self.ratios.len() - 1) as f64).exp())
+    }
+
+    // Catmull-Rom interpolation in ln r, constant before the first radius
+    fn ratio(&self, radius: f64) -> f64 {
+        let last = self.ratios.len() - 1;
+        let position = ((radius.ln() - self.first) / self.step).clamp(0.0, last as f64);
+        let k = (position.floor() as usize).min(last - 1);
+        let t = position - k as f64;
+        let value = |i: isize| self.ratios[i.clamp(0, last as isize) as usize];
+        let k = k as isize;
+        let (p0, p1, p2, p3) = (value(k - 1), value(k), value(k + 1), value(k + 2));
+        p1 + 0.5 * t * (p2 - p0 + t * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3 + t * (3.0 * (p1 - p2) + p3 - p0)))
+    }
+}
+
+impl ContractedHalo {
+    pub fn new(
+        halo: Box<dyn MassComponent>,
+        disk: Box<dyn MassComponent>,
+        orbits: (f64, f64),
+        virial_radius: Option<f64>,
+    ) -> Self {
+        ContractedHalo {
+            halo,
+            disk,
+            orbits,
+            virial_radius,
+            profile: OnceLock::new(),
+            moved_potential: OnceLock::new(),
+        }
+    }
+
+    // Orbit-averaged radius r̄ of a shell at the radius
+    fn orbit_radius(&self, radius: f64, virial_radius: f64) -> f64 {
+        let (scale, exponent) = self.orbits;
+        scale * virial_radius * (radius / virial_radius).powf(exponent)
+    }
+
+    // Radius of the shell whose orbit-averaged radius it is
+    fn shell_radius(&self, orbit_radius: f64, virial_radius: f64) -> f64 {
+        let (scale, exponent) = self.orbits;
+        virial_radius * (orbit_radius / (scale * virial_radius)).powf(1.0 / exponent)
+    }
+
+    fn profile(&self) -> Option<&ContractedProfile> {
+        self.profile
+            .get_or_init(|| {
+                // Without virial values there is no redshift, r₂₀₀ is that of today
+                let virial_radius = self.virial_radius.or_else(|| r200(|r| self.halo.mass(r), 0.0))?;
+                Some(self.contracted_profile(virial_radius))
+            })
+            .as_ref()
+    }
+
+    // Solve for the initial radius of the shell at each stored radius, starting from that of the previous one
+    fn contracted_profile(&self, virial_radius: f64) -> ContractedProfile {
+        let (first, last) = CONTRACTION_RANGE;
+        let nodes = ((last / first).log10() * MASS_NODES_PER_DECADE as f64).ceil() as usize;
+        let first = (first * virial_radius).ln();
+        let step = (last / CONTRACTION_RANGE.0).ln() / nodes as f64;
+
+        // Baryons within the virial radius, initially distributed like the dark matter
+        let baryons = self.disk.mass(virial_radius);
+        let virial_mass = self.halo.mass(virial_radius);
+
+        let mut expansion = 1.0;
+        let ratios = (0..=nodes)
+            .map(|k| {
+                let radius = (first + step * k as f64).exp();
+                let shell = self.shell_radius(radius, virial_radius);
+                let disk_mass = self.disk.mass(self.orbit_radius(shell, virial_radius));
+
+                // r_i · (M_DM + M_b)(r̄_i) - r_f · (M_Disk(r̄_f) + M_DM(r̄_i)), increasing with r_i
+                let excess = |log_initial: f64| {
+                    let initial = log_initial.exp();
+                    let dark = self.halo.mass(self.orbit_radius(initial, virial_radius));
+                    let initial_baryons = baryons * (dark / virial_mass).min(1.0);
+                    initial * (dark + initial_baryons) - shell * (disk_mass + dark)
+                };
+
+                let initial = find_root(excess, (shell * expansion).ln()).map(f64::exp);
+                let dark = match initial {
+                    Some(initial) => {
+                        expansion = initial / shell;
+                        self.halo.mass(self.orbit_radius(initial, virial_radius))
+                    }
+                    None => self.halo.mass(radius),
+                };
+                (dark / self.halo.mass(radius)).ln()
+            })
+            .collect();
+
+        ContractedProfile {
+            first,
+            step,
+            ratios,
+        }
+    }
+
+    // Mass moved inwards by the contraction over r², in kg/m³ * kpc
+    fn moved_mass(&self, radius: f64) -> f64 {
+        (self.mass(radius) - self.halo.mass(radius)) / halo_factor() / radius.powi(2)
+    }
+
+    // The integral is split at the stored radii, as the moved mass has a kink near the virial radius
+    fn moved_potential(&self) -> &[(f64, f64)] {
+        self.moved_potential.get_or_init(|| {
+            let Some(profile) = self.profile() else {
+                return Vec::new();
+            };
+            let (first, last) = profile.range();
+            let nodes = ((last / first).log10() * POTENTIAL_NODES_PER_DECADE as f64).ceil() as usize;
+            let radii: Vec<f64> = (0..=nodes)
+                .map(|i| first * (last / first).powf(i as f64 / nodes as f64))
+                .collect();
+
+            let mut integral = 0.0;
+            let mut table = vec![(radii[nodes], integral)];
+            for pair in radii.windows(2).rev() {
+                integral += integrate(|r| self.moved_mass(r), pair[0], pair[1]);
+                table.push((pair[0], integral));
+            }
+            table.reverse();
+            table
+        })
+    }
+}
+
+// Root of an increasing function near a guess, by the Illinois variant of regula falsi
+fn find_root(f: impl Fn(f64) -> f64, guess: f64) -> Option<f64> {
+    // Widen the bracket around the guess in steps of ln 1.5
+    let (mut low, mut high) = (guess, guess);
+    let (mut f_low, mut f_high) = (f(low), f(high));
+    for _ in 0..100 {
+        if f_low <= 0.0 {
+            break;
+        }
+        high = low;
+        f_high = f_low;
+        low -= 0.4;
+        f_low = f(low);
+    }
+    for _ in 0..100 {
+        if f_high >= 0.0 {
+            break;
+        }
+        low = high;
+        f_low = f_high;
+        high += 0.4;
+        f_high = f(high);
+    }
+    if !(f_low <= 0.0 && f_high >= 0.0) {
+        return None;
+    }
+
+    let mut side = 0;
+    for _ in 0..100 {
+        if f_low == 0.0 || high - low < 1.0e-13 {
+            return Some(low);
+        }
+        if f_high == 0.0 {
+            return Some(high);
+        }
+        let mid = (low * f_high - high * f_low) / (f_high - f_low);
+        let f_mid = f(mid);
+        if f_mid > 0.0 {
+            high = mid;
+            f_high = f_mid;
+            // Halve the value at the end which stays, so it does not stay forever
+            if side == -1 {
+                f_low /= 2.0;
+            }
+            side = -1;
+        } else {
+            low = mid;
+            f_low = f_mid;
+            if side == 1 {
+                f_high /= 2.0;
+            }
+            side = 1;
+        }
+    }
+    Some(0.5 * (low + high))
+}
+
+impl MassComponent for ContractedHalo {
+    fn name(&self) -> &'static str {
+        self.halo.name()
+    }
+
+    fn density(&self, radius: f64) -> f64 {
+        let step = DENSITY_STEP * radius.max(1.0);
+        let (lower, upper) = ((radius - step).max(0.0), radius + step);
+        let slope = (self.mass(upper) - self.mass(lower)) / (upper - lower) / halo_factor();
+        slope / (4.0 * PI * radius.max(step).powi(2)) * factor_kpc_scaled()
+    }
+
+    // The dark matter within r̄_f is the one initially within r̄_i. Beyond the stored radii
+    // the disk holds all its mass and the shells are back at their initial radii.
+    fn mass(&self, radius: f64) -> f64 {
+        let halo = self.halo.mass(radius);
+        match self.profile() {
+            Some(profile) if radius > 0.0 && radius < profile.range().1 => halo * profile.ratio(radius).exp(),
+            _ => halo,
+        }
+    }
+
+    fn velocity(&self, radius: f64) -> f64 {
+        if radius == 0.0 {
+            return 0.0;
+        }
+        (self.mass(radius) / dynamical_mass(radius, 1.0)).sqrt()
+    }
+
+    // Potential of the halo plus that of the mass moved by the contraction
+    fn potential(&self, radius: f64) -> f64 {
+        // Integrated up to the next stored radius
+        let moved = match self.moved_potential().iter().find(|&&(node, _)| node >= radius) {
+            Some(&(node, integral)) => integral + integrate(|r| self.moved_mass(r), radius, node),
+            None => 0.0,
+        };
+        self.halo.potential(radius) - G * moved * kpc2_to_km2()
+    }
+}
+
+// Combined velocity of all components, they add in quadrature
+pub fn total_velocity(components: &[Box<dyn MassComponent>], radius: f64) -> f64 {
+    components
+        .iter()
+        .map(|component| component.velocity(radius).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+// Combined enclosed mass of all components
+pub fn total_mass(components: &[Box<dyn MassComponent>], radius: f64) -> f64 {
+    components.iter().map(|component| component.mass(radius)).sum()
+}
+
+// Combined potential of all components
+pub fn total_potential(components: &[Box<dyn MassComponent>], radius: f64) -> f64 {
+    components.iter().map(|component| component.potential(radius)).sum()
+}
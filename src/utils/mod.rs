@@ -1,6 +1,26 @@
 pub mod calculate_velocity;
 pub mod calculate_mass;
 pub mod calculate_density;
-pub mod intersection;
+pub mod calculate_potential;
+pub mod mass_component;
+pub mod galaxy_model;
+pub mod quadrature;
+pub mod numerical_profile;
+pub mod expression;
+pub mod radial_grid;
+pub mod axis_settings;
+pub mod chart_layout;
+pub mod cosmology;
+pub mod evaluation_radius;
+pub mod particles;
+pub mod orbit;
+pub mod inversion;
+pub mod rar;
+pub mod tully_fisher;
+pub mod curve_features;
+pub mod clipping;
+pub mod reference_line;
 pub mod bessel;
+pub mod observed;
+pub mod fit;
 pub mod translation;
\ No newline at end of file
@@ -0,0 +1,190 @@
+// Mass, velocity and potential of arbitrary density profiles by numerical integration.
+// Densities are in kg/m³ (spherical) or kg/m² (thin disk), radii in kpc,
+// like the closed forms in calculate_mass.rs, which serve as cross-checks.
+use scilib::constant::G;
+use std::f64::consts::PI;
+use crate::utils::{
+    calculate_density::factor_kpc_scaled,
+    calculate_mass::{disk_factor, halo_factor},
+    calculate_potential::{kpc2_to_km2, kpc_to_km2},
+    mass_component::MassComponent,
+    quadrature::{elliptic_k, integrate, integrate_to_infinity},
+};
+
+// Relative step of the numerical derivative of the disk potential
+const DERIVATIVE_STEP: f64 = 1.0e-3;
+
+// Enclosed mass 4π ∫ ρ r² dr of a spherical profile, in kg/m³ * kpc³
+pub fn spherical_mass(density: impl Fn(f64) -> f64, radius: f64) -> f64 {
+    4.0 * PI * integrate(|r| density(r) * r.powi(2), 0.0, radius)
+}
+
+// Circular velocity of a spherical profile in km/s
+pub fn spherical_velocity(density: impl Fn(f64) -> f64, radius: f64) -> f64 {
+    if radius == 0.0 {
+        return 0.0;
+    }
+    (G * spherical_mass(density, radius) / radius * kpc2_to_km2()).sqrt()
+}
+
+// Potential of a spherical profile in (km/s)², zero at the reference radius (may be infinite)
+pub fn spherical_potential(density: impl Fn(f64) -> f64, radius: f64, reference: f64) -> f64 {
+    let inner = if radius == 0.0 {
+        0.0
+    } else {
+        spherical_mass(&density, radius) / radius
+    };
+
+    // Φ(r) - Φ(R) = -G (M(r)/r - M(R)/R + 4π ∫_r^R ρ r' dr')
+    let outer = if reference.is_infinite() {
+        4.0 * PI * integrate_to_infinity(|r| density(r) * r, radius, radius.max(1.0))
+    } else {
+        4.0 * PI * integrate(|r| density(r) * r, radius, reference)
+            - spherical_mass(&density, reference) / reference
+    };
+
+    -G * (inner + outer) * kpc2_to_km2()
+}
+
+// Mass 2π ∫ Σ R dR of a thin disk, in kg/m² * kpc²
+pub fn disk_mass(surface_density: impl Fn(f64) -> f64, radius: f64) -> f64 {
+    2.0 * PI * integrate(|r| surface_density(r) * r, 0.0, radius)
+}
+
+// Potential of a thin disk in its plane in (km/s)², zero at infinity.
+// Sum over rings: Φ(R) = -4G ∫ Σ(R') R' / (R + R') K(k) dR', with k² = 4RR' / (R + R')²
+pub fn disk_potential(surface_density: impl Fn(f64) -> f64, radius: f64) -> f64 {
+    if radius == 0.0 {
+        return -2.0 * PI * G * integrate_to_infinity(&surface_density, 0.0, 1.0) * kpc_to_km2();
+    }
+
+    // K has a logarithmic singularity at R' = R, so integrate both sides separately
+    let ring = |r: f64| {
+        let k_complement = (radius - r).abs() / (radius + r);
+        surface_density(r) * r / (radius + r) * elliptic_k(k_complement)
+    };
+    let inner = integrate(ring, 0.0, radius);
+    let outer = integrate(|u: f64| ring(radius / u) * radius / u.powi(2), 0.0, 1.0);
+
+    -4.0 * G * (inner + outer) * kpc_to_km2()
+}
+
+// Circular velocity of a thin disk in km/s, from v² = R dΦ/dR
+pub fn disk_velocity(surface_density: impl Fn(f64) -> f64, radius: f64) -> f64 {
+    if radius == 0.0 {
+        return 0.0;
+    }
+
+    // Five point stencil
+    let h = DERIVATIVE_STEP * radius;
+    let phi = |r: f64| disk_potential(&surface_density, r);
+    let derivative = (phi(radius - 2.0 * h) - 8.0 * phi(radius - h) + 8.0 * phi(radius + h)
+        - phi(radius + 2.0 * h))
+        / (12.0 * h);
+
+    (radius * derivative).max(0.0).sqrt()
+}
+
+// Spherical component defined only by its density function in kg/m³
+pub struct SphericalProfile<F> {
+    pub name: &'static str,
+    pub density: F,
+    // Radius where the potential is zero, infinite if the mass converges
+    pub reference: f64,
+}
+
+impl<F: Fn(f64) -> f64 + Send + Sync> MassComponent for SphericalProfile<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn density(&self, radius: f64) -> f64 {
+        (self.density)(radius) * factor_kpc_scaled()
+    }
+
+    fn mass(&self, radius: f64) -> f64 {
+        spherical_mass(&self.density, radius) * halo_factor()
+    }
+
+    fn velocity(&self, radius: f64) -> f64 {
+        spherical_velocity(&self.density, radius)
+    }
+
+    fn potential(&self, radius: f64) -> f64 {
+        spherical_potential(&self.density, radius, self.reference)
+    }
+}
+
+// Thin axisymmetric disk defined only by its surface density function in kg/m²
+pub struct ThinDiskProfile<F> {
+    pub name: &'static str,
+    pub surface_density: F,
+}
+
+impl<F: Fn(f64) -> f64 + Send + Sync> MassComponent for ThinDiskProfile<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn density(&self, radius: f64) -> f64 {
+        (self.surface_density)(radius)
+    }
+
+    // Same convention as mass_disk, with a width of d = 0.5
+    fn mass(&self, radius: f64) -> f64 {
+        0.5 * disk_mass(&self.surface_density, radius) * disk_factor()
+    }
+
+    fn velocity(&self, radius: f64) -> f64 {
+        disk_velocity(&self.surface_density, radius)
+    }
+
+    fn potential(&self, radius: f64) -> f64 {
+        disk_potential(&self.surface_density, radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::galaxy_model::{GalaxyModel, HaloProfile};
+
+    // Relative deviation allowed between the closed forms and the numerical integration
+    const TOLERANCE: f64 = 1.0e-6;
+    const RADII: [f64; 6] = [0.5, 2.0, 5.0, 10.0, 20.0, 40.0];
+
+    fn assert_close(actual: f64, expected: f64, what: &str) {
+        assert!(
+            (actual - expected).abs() <= TOLERANCE * expected.abs(),
+            "{}: {} != {}",
+            what,
+            actual,
+            expected
+        );
+    }
+
+    // Mass, velocity and potential of each closed form component against its numerical counterpart
+    fn assert_matches_closed_forms(model: &GalaxyModel) {
+        let closed = model.components();
+        let numerical = model.numerical_components();
+        for (closed, numerical) in closed.iter().zip(&numerical) {
+            for radius in RADII {
+                let what = |quantity: &str| format!("{} {} at {} kpc", closed.name(), quantity, radius);
+                assert_close(numerical.mass(radius), closed.mass(radius), &what("mass"));
+                assert_close(numerical.velocity(radius), closed.velocity(radius), &what("velocity"));
+                assert_close(numerical.potential(radius), closed.potential(radius), &what("potential"));
+            }
+        }
+    }
+
+    #[test]
+    fn disk_and_isothermal_halo() {
+        assert_matches_closed_forms(&GalaxyModel::default());
+    }
+
+    #[test]
+    fn nfw_halo() {
+        let mut model = GalaxyModel::default();
+        model.halo.profile = HaloProfile::Nfw;
+        assert_matches_closed_forms(&model);
+    }
+}
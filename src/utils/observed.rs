@@ -0,0 +1,202 @@
+// Observed rotation curves, read from plain text files
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ObservedPoint {
+    pub radius: f64,
+    pub velocity: f64,
+    pub error: Option<f64>,
+}
+
+impl ObservedPoint {
+    // Point without an error
+    pub fn new(radius: f64, velocity: f64) -> Self {
+        Self {
+            radius,
+            velocity,
+            error: None,
+        }
+    }
+}
+
+// Rotation curve of NGC 3198
+pub fn ngc_3198() -> Vec<ObservedPoint> {
+    vec![
+        ObservedPoint::new(0.0, 0.0),
+        ObservedPoint::new(1.0, 55.0),
+        ObservedPoint::new(2.0, 92.0),
+        ObservedPoint::new(3.0, 110.0),
+        ObservedPoint::new(4.0, 123.0),
+        ObservedPoint::new(5.0, 134.0),
+        ObservedPoint::new(6.0, 142.0),
+        ObservedPoint::new(7.0, 145.0),
+        ObservedPoint::new(8.0, 147.0),
+        ObservedPoint::new(9.0, 148.0),
+        ObservedPoint::new(10.0, 152.0),
+        ObservedPoint::new(11.0, 155.0),
+        ObservedPoint::new(12.0, 156.0),
+        ObservedPoint::new(13.0, 157.0),
+        ObservedPoint::new(14.0, 153.0),
+        ObservedPoint::new(15.0, 154.0),
+        ObservedPoint::new(16.0, 153.0),
+        ObservedPoint::new(17.0, 150.0),
+        ObservedPoint::new(18.0, 149.0),
+        ObservedPoint::new(19.0, 148.0),
+        ObservedPoint::new(20.0, 146.0),
+        ObservedPoint::new(21.0, 147.0),
+        ObservedPoint::new(22.0, 148.0),
+        ObservedPoint::new(23.0, 148.0),
+        ObservedPoint::new(24.0, 149.0),
+        ObservedPoint::new(25.0, 150.0),
+        ObservedPoint::new(26.0, 150.0),
+        ObservedPoint::new(27.0, 149.0),
+    ]
+}
+
+// Parse "radius velocity [error]" lines, separated by commas, semicolons or whitespace.
+// Empty lines, lines starting with '#' and non-numeric header lines are skipped.
+pub fn parse_observed(input: &str) -> Result<Vec<ObservedPoint>, String> {
+    let mut points = Vec::new();
+
+    for (line_i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|field| !field.is_empty())
+            .collect();
+
+        let values: Result<Vec<f64>, _> = fields.iter().map(|field| field.parse::<f64>()).collect();
+        let values = match values {
+            Ok(values) => values,
+            // Allow header lines in front of the data
+            Err(_) if points.is_empty() => continue,
+            Err(err) => return Err(format!("line {}: {}", line_i + 1, err)),
+        };
+
+        if values.len() < 2 {
+            return Err(format!("line {}: expected radius and velocity", line_i + 1));
+        }
+
+        points.push(ObservedPoint {
+            radius: values[0],
+            velocity: values[1],
+            error: values.get(2).copied().filter(|err| *err > 0.0),
+        });
+    }
+
+    if points.is_empty() {
+        return Err("no data points found".to_string());
+    }
+
+    // Radii may be listed in any order
+    points.sort_by(|a, b| a.radius.total_cmp(&b.radius));
+
+    Ok(points)
+}
+
+// How an observed curve is drawn between its radii
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+    // Straight lines between the points
+    #[default]
+    Interpolated,
+    // Value of the closest point, changing halfway between two radii
+    Nearest,
+}
+
+// Observed velocity at a radius, NaN outside of the observed radii.
+// The points have to be sorted by radius.
+pub fn observed_at(points: &[ObservedPoint], radius: f64, alignment: Alignment) -> f64 {
+    match (points.first(), points.last()) {
+        (Some(first), Some(last)) if radius >= first.radius && radius < last.radius => {}
+        (_, Some(last)) if radius == last.radius => return last.velocity,
+        _ => return f64::NAN,
+    }
+
+    // Points around the radius
+    let next_i = points.partition_point(|point| point.radius <= radius);
+    let (prev, next) = (&points[next_i - 1], &points[next_i]);
+
+    match alignment {
+        Alignment::Interpolated => {
+            let t = (radius - prev.radius) / (next.radius - prev.radius);
+            prev.velocity + t * (next.velocity - prev.velocity)
+        }
+        // The outer point from halfway on
+        Alignment::Nearest if radius - prev.radius < next.radius - radius => prev.velocity,
+        Alignment::Nearest => next.velocity,
+    }
+}
+
+// Radii the chart needs to draw the observed curve exactly: the observed radii,
+// and for the nearest point also the radii halfway between them, where the value changes
+pub fn alignment_radii(points: &[ObservedPoint], alignment: Alignment) -> Vec<f64> {
+    let mut radii: Vec<f64> = points.iter().map(|point| point.radius).collect();
+    if alignment == Alignment::Nearest {
+        radii.extend(points.windows(2).map(|pair| (pair[0].radius + pair[1].radius) / 2.0));
+        radii.sort_by(f64::total_cmp);
+    }
+    radii
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_comments_and_headers() {
+        let input = "# NGC 3198\nradius velocity error\n\n1.0, 50.0, 5.0\n2.0;80.0\n3.0 100.0 0\n";
+        let points = parse_observed(input).unwrap();
+        assert_eq!(
+            points,
+            vec![
+                ObservedPoint { radius: 1.0, velocity: 50.0, error: Some(5.0) },
+                ObservedPoint { radius: 2.0, velocity: 80.0, error: None },
+                ObservedPoint { radius: 3.0, velocity: 100.0, error: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        assert_eq!(parse_observed("1.0 50.0\nfoo bar"), Err("line 2: invalid float literal".to_string()));
+        assert_eq!(parse_observed("1.0 50.0\n2.0"), Err("line 2: expected radius and velocity".to_string()));
+        assert_eq!(parse_observed("# empty\n"), Err("no data points found".to_string()));
+    }
+
+    #[test]
+    fn sorts_by_radius() {
+        let points = parse_observed("3.0 100.0
+1.0 50.0
+2.0 80.0
+").unwrap();
+        let radii: Vec<f64> = points.iter().map(|point| point.radius).collect();
+        assert_eq!(radii, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn observed_at_the_alignments() {
+        let points = [ObservedPoint::new(1.0, 50.0), ObservedPoint::new(3.0, 100.0), ObservedPoint::new(4.0, 90.0)];
+
+        assert_eq!(observed_at(&points, 2.0, Alignment::Interpolated), 75.0);
+        assert_eq!(observed_at(&points, 3.5, Alignment::Interpolated), 95.0);
+        assert_eq!(observed_at(&points, 1.9, Alignment::Nearest), 50.0);
+        assert_eq!(observed_at(&points, 2.0, Alignment::Nearest), 100.0);
+        assert_eq!(observed_at(&points, 4.0, Alignment::Nearest), 90.0);
+
+        // Nothing outside of the observed radii
+        assert!(observed_at(&points, 0.5, Alignment::Interpolated).is_nan());
+        assert!(observed_at(&points, 4.5, Alignment::Nearest).is_nan());
+        assert!(observed_at(&[], 1.0, Alignment::Interpolated).is_nan());
+    }
+
+    #[test]
+    fn nearest_alignment_adds_the_midpoints() {
+        let points = [ObservedPoint::new(1.0, 50.0), ObservedPoint::new(3.0, 100.0), ObservedPoint::new(4.0, 90.0)];
+
+        assert_eq!(alignment_radii(&points, Alignment::Interpolated), vec![1.0, 3.0, 4.0]);
+        assert_eq!(alignment_radii(&points, Alignment::Nearest), vec![1.0, 2.0, 3.0, 3.5, 4.0]);
+    }
+}
@@ -0,0 +1,161 @@
+// Orbits of test stars in the plane of the galaxy
+use crate::utils::{calculate_velocity::kpc_per_km_s_to_myr, mass_component::MassComponent};
+use std::f64::consts::PI;
+
+// Time steps per circular orbit at the starting radius
+const STEPS_PER_ORBIT: f64 = 500.0;
+// Largest number of steps, long orbits are integrated with longer steps instead
+const MAX_STEPS: usize = 20000;
+// Largest number of returned samples, the integration itself is not thinned
+const MAX_SAMPLES: usize = 2000;
+
+// Limits of the inputs
+pub const DURATION_LIMITS: (f64, f64) = (10.0, 20000.0);
+pub const VELOCITY_LIMITS: (f64, f64) = (-1000.0, 1000.0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitSettings {
+    // Start on the x axis (kpc)
+    pub radius: f64,
+    // Initial velocity along and perpendicular to the radius (km/s)
+    pub radial_velocity: f64,
+    pub tangential_velocity: f64,
+    // Time span (Myr)
+    pub duration: f64,
+}
+
+impl Default for OrbitSettings {
+    fn default() -> Self {
+        Self {
+            radius: 8.0,
+            radial_velocity: 40.0,
+            tangential_velocity: 150.0,
+            duration: 2000.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitSample {
+    // Myr
+    pub time: f64,
+    // Position in kpc
+    pub x: f64,
+    pub y: f64,
+    // Change of the energy since the start relative to the energy scale of the orbit
+    pub energy_error: f64,
+}
+
+// Total potential in (km/s)²
+fn potential(components: &[Box<dyn MassComponent>], radius: f64) -> f64 {
+    components.iter().map(|component| component.potential(radius)).sum()
+}
+
+// Squared circular velocity in (km/s)²
+fn circular_velocity_squared(components: &[Box<dyn MassComponent>], radius: f64) -> f64 {
+    components.iter().map(|component| component.velocity(radius).powi(2)).sum()
+}
+
+// Radial acceleration in (km/s)²/kpc
+fn acceleration(components: &[Box<dyn MassComponent>], radius: f64) -> f64 {
+    // v² / r = dΦ/dr for circular orbits
+    -circular_velocity_squared(components, radius) / radius
+}
+
+// Integrate the orbit with the leapfrog (kick-drift-kick) scheme, which keeps the energy
+// bounded over many orbits. Stops early if the star hits the center.
+pub fn integrate_orbit(components: &[Box<dyn MassComponent>], settings: &OrbitSettings) -> Vec<OrbitSample> {
+    // Distances in kpc, velocities in km/s, times in Myr
+    let kpc_per_myr = 1.0 / kpc_per_km_s_to_myr();
+    let (mut x, mut y) = (settings.radius, 0.0);
+    let (mut vx, mut vy) = (settings.radial_velocity, settings.tangential_velocity);
+
+    let energy = |x: f64, y: f64, vx: f64, vy: f64| {
+        0.5 * (vx.powi(2) + vy.powi(2)) + potential(components, x.hypot(y))
+    };
+    let initial_energy = energy(x, y, vx, vy);
+    // The potential may be zero anywhere (isothermal halos are zeroed at 200 kpc), so the energy
+    // itself is no scale; the circular velocity keeps it positive where the star starts at rest
+    let circular = circular_velocity_squared(components, settings.radius);
+    let energy_scale = (0.5 * (vx.powi(2) + vy.powi(2)) + potential(components, settings.radius).abs()).max(circular);
+    let relative_error = |current: f64| ((current - initial_energy) / energy_scale).abs();
+
+    // A fixed step keeps the leapfrog scheme symplectic, it follows from the period at the start
+    let duration = settings.duration.clamp(DURATION_LIMITS.0, DURATION_LIMITS.1);
+    let period = 2.0 * PI * settings.radius / circular.sqrt() * kpc_per_km_s_to_myr();
+    let time_step = if period.is_finite() && period > 0.0 {
+        (period / STEPS_PER_ORBIT).max(duration / MAX_STEPS as f64)
+    } else {
+        duration / MAX_STEPS as f64
+    };
+    let steps = ((duration / time_step).ceil() as usize).min(MAX_STEPS);
+    let every = steps.div_ceil(MAX_SAMPLES).max(1);
+
+    let mut samples = vec![OrbitSample { time: 0.0, x, y, energy_error: 0.0 }];
+
+    // Acceleration components in km/s per Myr
+    let kick = |x: f64, y: f64| {
+        let radius = x.hypot(y);
+        let a = acceleration(components, radius) * kpc_per_myr;
+        (a * x / radius, a * y / radius)
+    };
+    let (mut ax, mut ay) = kick(x, y);
+
+    for step in 1..=steps {
+        vx += 0.5 * time_step * ax;
+        vy += 0.5 * time_step * ay;
+        x += time_step * vx * kpc_per_myr;
+        y += time_step * vy * kpc_per_myr;
+        (ax, ay) = kick(x, y);
+        vx += 0.5 * time_step * ax;
+        vy += 0.5 * time_step * ay;
+
+        if !(ax.is_finite() && ay.is_finite() && x.is_finite() && y.is_finite()) {
+            break;
+        }
+
+        if step % every == 0 || step == steps {
+            samples.push(OrbitSample {
+                time: step as f64 * time_step,
+                x,
+                y,
+                energy_error: relative_error(energy(x, y, vx, vy)),
+            });
+        }
+    }
+
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::galaxy_model::GalaxyModel;
+
+    #[test]
+    fn energy_error_stays_bounded() {
+        let components = GalaxyModel::default().components();
+        let settings = OrbitSettings { duration: DURATION_LIMITS.1, ..OrbitSettings::default() };
+        let samples = integrate_orbit(&components, &settings);
+        assert_eq!(samples.last().unwrap().time, DURATION_LIMITS.1);
+
+        // The leapfrog error oscillates instead of drifting: the second half of the orbit
+        // is no worse than the first
+        let max_error = |samples: &[OrbitSample]| samples.iter().map(|sample| sample.energy_error).fold(0.0, f64::max);
+        let (first, second) = samples.split_at(samples.len() / 2);
+        assert!(max_error(&samples) < 1e-3, "energy error {}", max_error(&samples));
+        assert!(max_error(second) < 2.0 * max_error(first));
+    }
+
+    #[test]
+    fn circular_orbits_keep_their_radius() {
+        let components = GalaxyModel::default().components();
+        let radius = 8.0;
+        let velocity: f64 = components.iter().map(|component| component.velocity(radius).powi(2)).sum::<f64>().sqrt();
+        let settings = OrbitSettings { radius, radial_velocity: 0.0, tangential_velocity: velocity, duration: 5000.0 };
+
+        for sample in integrate_orbit(&components, &settings) {
+            assert!((sample.x.hypot(sample.y) - radius).abs() < 1e-3 * radius, "radius {}", sample.x.hypot(sample.y));
+        }
+    }
+}
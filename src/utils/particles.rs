@@ -0,0 +1,144 @@
+// Particles of the animated face-on galaxy
+use crate::utils::calculate_velocity::kpc_per_km_s_to_myr;
+use std::f64::consts::PI;
+
+// Share of the particles placed along the spiral arms
+const ARM_FRACTION: f64 = 0.3;
+const ARMS: usize = 2;
+// Angle by which the arms wind per disk scale length
+const ARM_WINDING: f64 = 0.6;
+// Spread of the arm particles around the arm (rad)
+const ARM_WIDTH: f64 = 0.25;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    // Position in kpc and rad
+    pub radius: f64,
+    pub angle: f64,
+    // Angular velocity in rad/Myr
+    pub omega: f64,
+    // Part of a spiral arm, drawn highlighted to show the winding
+    pub arm: bool,
+}
+
+// Small xorshift generator, so the same seed gives the same galaxy
+struct Random(u64);
+
+impl Random {
+    // Uniform in (0, 1]
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        ((self.0 >> 11) as f64 + 1.0) / (1u64 << 53) as f64
+    }
+}
+
+// Radii at which the enclosed mass of the disk is tabulated for placing the particles
+const MASS_SAMPLES: usize = 200;
+
+// Radius below which the given share of the mass lies, interpolated in the table of the
+// enclosed mass; the particles are uniform in area if the disk holds no mass
+fn radius_of_share(table: &[(f64, f64)], share: f64) -> f64 {
+    let (max_radius, total) = table[table.len() - 1];
+    if total <= 0.0 {
+        return max_radius * share.sqrt();
+    }
+
+    let target = share * total;
+    let i = table.partition_point(|&(_, mass)| mass < target).clamp(1, table.len() - 1);
+    let ((r1, m1), (r2, m2)) = (table[i - 1], table[i]);
+    if m2 > m1 {
+        r1 + (target - m1) / (m2 - m1) * (r2 - r1)
+    } else {
+        r1
+    }
+}
+
+// Particles distributed like the surface density of the disk up to the maximum radius, by inverting
+// its enclosed mass (10^10 M☉). The velocity function gives the circular velocity in km/s at a
+// radius in kpc, the scale length sets the winding of the arms.
+pub fn seed_particles(
+    count: usize,
+    scale: f64,
+    max_radius: f64,
+    seed: u64,
+    mass: impl Fn(f64) -> f64,
+    velocity: impl Fn(f64) -> f64,
+) -> Vec<Particle> {
+    let mut random = Random(seed.max(1));
+
+    // Kept increasing, a custom density may be negative somewhere
+    let mut enclosed = 0.0_f64;
+    let table: Vec<(f64, f64)> = (0..=MASS_SAMPLES)
+        .map(|i| {
+            let radius = max_radius * i as f64 / MASS_SAMPLES as f64;
+            let mass = mass(radius);
+            if mass.is_finite() {
+                enclosed = enclosed.max(mass);
+            }
+            (radius, enclosed)
+        })
+        .collect();
+
+    (0..count)
+        .map(|i| {
+            let radius = radius_of_share(&table, random.next());
+
+            let arm = (i as f64) < ARM_FRACTION * count as f64;
+            let angle = if arm {
+                // Trailing spiral starting at one of the arms
+                let start = (i % ARMS) as f64 * 2.0 * PI / ARMS as f64;
+                start + ARM_WINDING * radius / scale + ARM_WIDTH * (random.next() - 0.5)
+            } else {
+                2.0 * PI * random.next()
+            };
+
+            Particle {
+                radius,
+                angle,
+                omega: angular_velocity(radius, velocity(radius)),
+                arm,
+            }
+        })
+        .collect()
+}
+
+// Angular velocity in rad/Myr from the circular velocity in km/s at a radius in kpc
+pub fn angular_velocity(radius: f64, velocity: f64) -> f64 {
+    if radius > 0.0 && velocity.is_finite() {
+        velocity / radius / kpc_per_km_s_to_myr()
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Enclosed mass of an exponential disk with unit scale length, up to a constant
+    fn exponential_disk(radius: f64) -> f64 {
+        1.0 - (1.0 + radius) * (-radius).exp()
+    }
+
+    #[test]
+    fn radii_follow_the_enclosed_mass() {
+        let particles = seed_particles(20000, 1.0, 10.0, 1, exponential_disk, |_| 200.0);
+        assert!(particles.iter().all(|particle| (0.0..=10.0).contains(&particle.radius)));
+
+        // Half of the mass of the disk lies within 1.678 scale lengths
+        let inner = particles.iter().filter(|particle| particle.radius < 1.678).count();
+        assert!((inner as f64 / 20000.0 - 0.5).abs() < 0.02, "{inner}");
+    }
+
+    #[test]
+    fn small_radii_and_empty_disks() {
+        // A radius far below the scale length, which a rejection sampler would rarely hit
+        let particles = seed_particles(100, 8.8, 0.1, 1, |r| exponential_disk(r / 8.8), |_| 200.0);
+        assert!(particles.iter().all(|particle| particle.radius <= 0.1));
+
+        let particles = seed_particles(100, 1.0, 5.0, 1, |_| 0.0, |_| 200.0);
+        assert!(particles.iter().all(|particle| particle.radius <= 5.0));
+    }
+}
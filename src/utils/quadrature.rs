@@ -0,0 +1,129 @@
+// Numerical integration for profiles without closed forms
+use std::f64::consts::FRAC_PI_2;
+
+// Relative accuracy at which the refinement stops
+const TOLERANCE: f64 = 1.0e-10;
+// Halving steps of the node spacing
+const MAX_LEVEL: usize = 8;
+// Nodes beyond this are too close to the endpoints to contribute
+const T_MAX: f64 = 4.0;
+
+// Tanh-sinh quadrature of f over [a, b].
+// Integrable singularities at the endpoints (e.g. log or 1/sqrt) are handled well,
+// so intervals should be split at known singular points.
+pub fn integrate(f: impl Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    if a == b {
+        return 0.0;
+    }
+    if b < a {
+        return -integrate(f, b, a);
+    }
+
+    let length = b - a;
+
+    // Sum of a node pair at t and -t; nodes are placed by their distance to the endpoints,
+    // so they stay accurate close to singularities. Non-finite values do not contribute.
+    let pair = |t: f64| -> f64 {
+        let u = FRAC_PI_2 * t.sinh();
+        let weight = FRAC_PI_2 * t.cosh() / u.cosh().powi(2);
+        let distance = length / (1.0 + (2.0 * u).exp());
+        let sum: f64 = [f(a + distance), f(b - distance)]
+            .iter()
+            .filter(|value| value.is_finite())
+            .sum();
+        weight * sum
+    };
+
+    let mut h = 1.0;
+    let center = f(a + length / 2.0);
+    let mut sum = FRAC_PI_2 * if center.is_finite() { center } else { 0.0 };
+    let mut k = 1;
+    while k as f64 * h <= T_MAX {
+        sum += pair(k as f64 * h);
+        k += 1;
+    }
+    let mut estimate = h * sum * length / 2.0;
+
+    for _ in 0..MAX_LEVEL {
+        h /= 2.0;
+
+        // Only the new (odd) nodes have to be evaluated
+        let mut k = 1;
+        while k as f64 * h <= T_MAX {
+            sum += pair(k as f64 * h);
+            k += 2;
+        }
+
+        let refined = h * sum * length / 2.0;
+        if (refined - estimate).abs() <= TOLERANCE * refined.abs() {
+            return refined;
+        }
+        estimate = refined;
+    }
+
+    estimate
+}
+
+// Integral of f over [a, ∞), which has to decay faster than 1/x.
+// The tail beyond a + scale is mapped onto (0, 1] with x = c / u.
+pub fn integrate_to_infinity(f: impl Fn(f64) -> f64, a: f64, scale: f64) -> f64 {
+    let c = a + scale;
+    integrate(&f, a, c) + integrate(|u: f64| f(c / u) * c / u.powi(2), 0.0, 1.0)
+}
+
+// Complete elliptic integral of the first kind K(k), given the complementary modulus k' = sqrt(1 - k²)
+pub fn elliptic_k(k_complement: f64) -> f64 {
+    if k_complement <= 0.0 {
+        return f64::INFINITY;
+    }
+
+    // Arithmetic-geometric mean
+    let (mut a, mut g) = (1.0_f64, k_complement);
+    while (a - g).abs() > 1.0e-15 * a {
+        (a, g) = ((a + g) / 2.0, (a * g).sqrt());
+    }
+    FRAC_PI_2 / a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= 1e-9 * expected.abs().max(1.0), "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn polynomial() {
+        assert_close(integrate(|x| x * x, 0.0, 1.0), 1.0 / 3.0);
+        assert_close(integrate(|x| x.powi(3) - x, -1.0, 2.0), 2.25);
+    }
+
+    #[test]
+    fn reversed_and_empty_interval() {
+        assert_close(integrate(|x| x * x, 1.0, 0.0), -1.0 / 3.0);
+        assert_eq!(integrate(|x| x * x, 2.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn endpoint_singularities() {
+        assert_close(integrate(|x| 1.0 / x.sqrt(), 0.0, 1.0), 2.0);
+        assert_close(integrate(|x| x.ln(), 0.0, 1.0), -1.0);
+    }
+
+    #[test]
+    fn infinite_interval() {
+        assert_close(integrate_to_infinity(|x| (-x).exp(), 0.0, 1.0), 1.0);
+        assert_close(integrate_to_infinity(|x| 1.0 / (x * x), 1.0, 1.0), 1.0);
+        assert_close(integrate_to_infinity(|x| 1.0 / (1.0 + x * x), 0.0, 1.0), PI / 2.0);
+    }
+
+    #[test]
+    fn elliptic_integral() {
+        // K(0) = π/2 and K(1/√2) = Γ(1/4)² / (4√π)
+        assert_close(elliptic_k(1.0), PI / 2.0);
+        assert_close(elliptic_k(0.5_f64.sqrt()), 1.854_074_677_301_372);
+        assert!(elliptic_k(0.0).is_infinite());
+    }
+}
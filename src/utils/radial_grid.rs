@@ -0,0 +1,255 @@
+// Radii at which the charts are sampled
+use serde::{Deserialize, Serialize};
+
+// Limits of the inputs
+pub const MAX_RADIUS_LIMITS: (f64, f64) = (0.1, 10000.0);
+pub const SAMPLES_LIMITS: (usize, usize) = (2, 5000);
+
+// First radius after the center with logarithmic spacing, relative to the maximum radius
+const LOG_MIN_FRACTION: f64 = 1.0e-3;
+
+// Adaptive sampling: start grid, allowed interpolation error relative to the range of each curve
+// and how often an interval may be halved
+const INITIAL_SAMPLES: usize = 17;
+const TOLERANCE: f64 = 2.0e-3;
+const MAX_DEPTH: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Spacing {
+    Linear,
+    // Dense close to the center, for profiles spanning several orders of magnitude
+    Logarithmic,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RadialGrid {
+    // Last radius in kpc
+    pub max_radius: f64,
+    // Number of radii, including the center; upper limit with adaptive sampling
+    pub samples: usize,
+    pub spacing: Spacing,
+    // Refine where the curves bend instead of using all samples
+    pub adaptive: bool,
+}
+
+impl Default for RadialGrid {
+    // 0 to 45.25 kpc in steps of 0.25 kpc
+    fn default() -> Self {
+        Self {
+            max_radius: 45.25,
+            samples: 182,
+            spacing: Spacing::Linear,
+            adaptive: true,
+        }
+    }
+}
+
+impl RadialGrid {
+    // All radii in kpc, starting at the center
+    pub fn radii(&self) -> Vec<f64> {
+        let max_radius = self.max_radius.clamp(MAX_RADIUS_LIMITS.0, MAX_RADIUS_LIMITS.1);
+        let samples = self.samples.clamp(SAMPLES_LIMITS.0, SAMPLES_LIMITS.1);
+
+        match self.spacing {
+            Spacing::Linear => {
+                let step = max_radius / (samples - 1) as f64;
+                (0..samples).map(|i| i as f64 * step).collect()
+            }
+            Spacing::Logarithmic => {
+                // The center can not be placed logarithmically, so it is added in front
+                let min_radius = max_radius * LOG_MIN_FRACTION;
+                let count = samples - 1;
+                let radius = |i: usize| {
+                    if count == 1 {
+                        max_radius
+                    } else {
+                        min_radius * (1.0 / LOG_MIN_FRACTION).powf(i as f64 / (count - 1) as f64)
+                    }
+                };
+                std::iter::once(0.0).chain((0..count).map(radius)).collect()
+            }
+        }
+    }
+
+    // Radii and values of f, which returns one value per curve.
+    // With adaptive sampling, intervals are halved until linear interpolation between
+    // their ends matches f at the midpoint, so steep parts get more points.
+    pub fn sample(&self, f: impl Fn(f64) -> Vec<f64>) -> Vec<(f64, Vec<f64>)> {
+        if !self.adaptive {
+            return self.radii().into_iter().map(|x| (x, f(x))).collect();
+        }
+
+        let max_samples = self.samples.clamp(SAMPLES_LIMITS.0, SAMPLES_LIMITS.1);
+        let initial = RadialGrid {
+            samples: INITIAL_SAMPLES.min(max_samples),
+            adaptive: false,
+            ..self.clone()
+        };
+        let mut points: Vec<(f64, Vec<f64>)> = initial.radii().into_iter().map(|x| (x, f(x))).collect();
+
+        // Range of each curve, the tolerance is relative to it
+        let curves = points.iter().map(|(_, ys)| ys.len()).max().unwrap_or(0);
+        let scales: Vec<f64> = (0..curves)
+            .map(|k| {
+                let scale = points
+                    .iter()
+                    .filter_map(|(_, ys)| ys.get(k))
+                    .filter(|y| y.is_finite())
+                    .fold(0.0_f64, |max, y| max.max(y.abs()));
+                if scale > 0.0 {
+                    scale
+                } else {
+                    1.0
+                }
+            })
+            .collect();
+
+        // Whether the interval starting at each point still has to be checked
+        let mut pending = vec![true; points.len() - 1];
+
+        // Refine level by level, so the limit of samples spreads evenly over the curve
+        for _ in 0..MAX_DEPTH {
+            let mut count = points.len();
+            let mut refined = Vec::with_capacity(2 * count);
+            let mut refined_pending = Vec::with_capacity(2 * count);
+
+            let last = points.pop();
+            for (i, (x0, y0)) in points.iter().enumerate() {
+                refined.push((*x0, y0.clone()));
+
+                let (x1, y1) = match points.get(i + 1).or(last.as_ref()) {
+                    Some((x1, y1)) if pending[i] && count < max_samples => (*x1, y1),
+                    _ => {
+                        refined_pending.push(false);
+                        continue;
+                    }
+                };
+
+                let xm = self.midpoint(*x0, x1);
+                let ym = f(xm);
+                // Position of the midpoint in the interval, below a half for the geometric mean
+                let t = (xm - x0) / (x1 - x0);
+                if needs_refinement(y0, &ym, y1, t, &scales) {
+                    refined.push((xm, ym));
+                    refined_pending.extend([true, true]);
+                    count += 1;
+                } else {
+                    refined_pending.push(false);
+                }
+            }
+            refined.extend(last);
+
+            let changed = refined.len() != points.len() + 1;
+            points = refined;
+            pending = refined_pending;
+            if !changed {
+                break;
+            }
+        }
+
+        points
+    }
+
+    // Midpoint of an interval, geometric with logarithmic spacing
+    fn midpoint(&self, x0: f64, x1: f64) -> f64 {
+        match self.spacing {
+            Spacing::Logarithmic if x0 > 0.0 => (x0 * x1).sqrt(),
+            _ => (x0 + x1) / 2.0,
+        }
+    }
+}
+
+// Whether any curve deviates from the straight line between the ends of an interval
+// at the midpoint, which lies at the share t of the interval.
+// Curves which are infinite at one end (e.g. the NFW cusp) are refined towards it. Curves
+// without a value at an end (e.g. Ω = v / r at the center) are not, there is nothing to approach.
+fn needs_refinement(y0: &[f64], ym: &[f64], y1: &[f64], t: f64, scales: &[f64]) -> bool {
+    y0.iter()
+        .zip(ym)
+        .zip(y1)
+        .zip(scales)
+        .any(|(((y0, ym), y1), scale)| {
+            if !ym.is_finite() || y0.is_nan() || y1.is_nan() {
+                return false;
+            }
+            match (y0.is_finite(), y1.is_finite()) {
+                (true, true) => (ym - (y0 + t * (y1 - y0))).abs() > TOLERANCE * scale,
+                (false, false) => false,
+                _ => true,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_radii(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (radius, expected) in actual.iter().zip(expected) {
+            assert!((radius - expected).abs() <= 1e-12 * expected.max(1.0), "{:?}", actual);
+        }
+    }
+
+    #[test]
+    fn default_steps_are_a_quarter_kpc() {
+        let radii = RadialGrid::default().radii();
+        assert_eq!(radii.len(), 182);
+        assert_eq!(radii[0], 0.0);
+        assert!((radii[1] - 0.25).abs() < 1e-12);
+        assert!((radii[181] - 45.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn logarithmic_spacing_starts_at_the_center() {
+        let grid = RadialGrid {
+            max_radius: 10.0,
+            samples: 5,
+            spacing: Spacing::Logarithmic,
+            adaptive: false,
+        };
+        assert_radii(&grid.radii(), &[0.0, 0.01, 0.1, 1.0, 10.0]);
+    }
+
+    #[test]
+    fn inputs_are_kept_within_their_limits() {
+        let grid = RadialGrid {
+            max_radius: 1.0e6,
+            samples: 1,
+            spacing: Spacing::Linear,
+            adaptive: false,
+        };
+        assert_radii(&grid.radii(), &[0.0, MAX_RADIUS_LIMITS.1]);
+    }
+
+    #[test]
+    fn straight_lines_are_not_refined() {
+        let points = RadialGrid::default().sample(|x| vec![2.0 * x + 1.0, -x]);
+        assert_eq!(points.len(), INITIAL_SAMPLES);
+    }
+
+    #[test]
+    fn undefined_values_are_not_refined() {
+        let grid = RadialGrid::default();
+        // NaN at the center only, like the angular velocity
+        let points = grid.sample(|x| vec![2.0 * x / x]);
+        assert_eq!(points.len(), INITIAL_SAMPLES);
+        assert!(points[0].1[0].is_nan());
+    }
+
+    #[test]
+    fn kinks_are_refined() {
+        let kink = 10.3;
+        let grid = RadialGrid::default();
+        let points = grid.sample(|x| vec![(x - kink).abs()]);
+
+        assert!(points.len() > INITIAL_SAMPLES && points.len() <= grid.samples);
+        assert!(points.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        // The samples close in on the kink (initially 2.8 kpc apart), the straight parts keep their spacing
+        let closest = points.iter().map(|(x, _)| (x - kink).abs()).fold(f64::INFINITY, f64::min);
+        assert!(closest < 0.1, "{}", closest);
+        let far = points.iter().filter(|(x, _)| (x - kink).abs() > 5.0).count();
+        assert!(far < INITIAL_SAMPLES, "{}", far);
+    }
+}
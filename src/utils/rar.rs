@@ -0,0 +1,37 @@
+// Radial acceleration relation: observed against baryonic centripetal acceleration
+use crate::utils::{calculate_velocity::kpc_to_m, mass_component::MassComponent, observed::ObservedPoint};
+
+// Acceleration scale of the empirical fit (m/s²), McGaugh, Lelli & Schombert 2016
+pub const ACCELERATION_SCALE: f64 = 1.2e-10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccelerationPoint {
+    // kpc
+    pub radius: f64,
+    // Centripetal accelerations in m/s²
+    pub observed: f64,
+    pub baryonic: f64,
+}
+
+// v²/r in m/s² for a velocity in km/s at a radius in kpc
+pub fn centripetal_acceleration(radius: f64, velocity: f64) -> f64 {
+    (velocity * 1000.0).powi(2) / (radius * kpc_to_m())
+}
+
+// Accelerations at the observed radii, the baryons being the modelled disk
+pub fn accelerations(observed: &[ObservedPoint], disk: &dyn MassComponent) -> Vec<AccelerationPoint> {
+    observed
+        .iter()
+        .filter(|point| point.radius > 0.0)
+        .map(|point| AccelerationPoint {
+            radius: point.radius,
+            observed: centripetal_acceleration(point.radius, point.velocity),
+            baryonic: centripetal_acceleration(point.radius, disk.velocity(point.radius)),
+        })
+        .collect()
+}
+
+// Empirical fitting function g_obs = g_bar / (1 - e^(-√(g_bar/g†)))
+pub fn rar_fit(baryonic: f64) -> f64 {
+    baryonic / (1.0 - (-(baryonic / ACCELERATION_SCALE).sqrt()).exp())
+}
@@ -0,0 +1,33 @@
+// Points of charts that compare markers with a reference line on (possibly logarithmic) axes
+
+// Points of the reference line
+const LINE_SAMPLES: usize = 50;
+
+// Smallest and largest positive value, None if there is none
+pub fn positive_range(values: impl IntoIterator<Item = f64>) -> Option<(f64, f64)> {
+    let (min, max) = values
+        .into_iter()
+        .filter(|value| value.is_finite() && *value > 0.0)
+        .fold((f64::INFINITY, 0.0_f64), |(min, max), value| (min.min(value), max.max(value)));
+    (min <= max).then_some((min, max))
+}
+
+// The line between both (positive) ends together with the markers, sorted by x.
+// The values of the line at x fill all columns, so the marker columns are NaN there.
+pub fn with_reference_line(
+    (min, max): (f64, f64),
+    line: impl Fn(f64) -> Vec<f64>,
+    markers: impl IntoIterator<Item = (f64, Vec<f64>)>,
+) -> Vec<(f64, Vec<f64>)> {
+    // Logarithmically spaced, so they are evenly spread on log axes
+    let (log_min, log_max) = (min.log10(), max.log10());
+    let mut points: Vec<(f64, Vec<f64>)> = (0..LINE_SAMPLES)
+        .map(|i| {
+            let x = 10.0_f64.powf(log_min + (log_max - log_min) * i as f64 / (LINE_SAMPLES - 1) as f64);
+            (x, line(x))
+        })
+        .collect();
+    points.extend(markers);
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    points
+}
@@ -0,0 +1,92 @@
+// Baryonic Tully-Fisher relation between the baryonic mass and the flat rotation velocity
+use crate::utils::{
+    evaluation_radius::OPTICAL_RADIUS_SCALES,
+    mass_component::{total_velocity, MassComponent},
+};
+
+// Slope of the relation M_b = A · v_f⁴, fixed as expected from MOND and found by McGaugh 2012
+pub const SLOPE: i32 = 4;
+// Radius of the total disk mass in scale lengths, where less than 10^-7 of it is left outside
+pub const TOTAL_MASS_SCALES: f64 = 20.0;
+// The flat velocity is averaged from one to two optical radii, in disk scale lengths
+pub const FLAT_RANGE_SCALES: (f64, f64) = (OPTICAL_RADIUS_SCALES, 2.0 * OPTICAL_RADIUS_SCALES);
+const FLAT_SAMPLES: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReferenceGalaxy {
+    pub name: &'static str,
+    // km/s
+    pub flat_velocity: f64,
+    // 10^10 M☉
+    pub baryonic_mass: f64,
+}
+
+// Rounded values of well measured disk galaxies, after the SPARC sample (Lelli, McGaugh & Schombert 2016):
+// name, flat velocity (km/s) and log10 of the baryonic mass (M☉)
+const REFERENCE_SAMPLE: [(&str, f64, f64); 15] = [
+    ("DDO 154", 47.0, 8.6),
+    ("DDO 170", 61.0, 9.0),
+    ("NGC 3109", 66.0, 8.9),
+    ("IC 2574", 66.0, 9.2),
+    ("NGC 1560", 77.0, 9.4),
+    ("NGC 6503", 116.0, 9.9),
+    ("UGC 128", 129.0, 10.2),
+    ("NGC 2403", 131.0, 10.0),
+    ("NGC 925", 136.0, 10.0),
+    ("NGC 3198", 150.0, 10.4),
+    ("NGC 5055", 179.0, 10.9),
+    ("NGC 2903", 185.0, 10.8),
+    ("NGC 7331", 239.0, 11.2),
+    ("NGC 2841", 285.0, 11.3),
+    ("UGC 2885", 290.0, 11.5),
+];
+
+pub fn reference_sample() -> Vec<ReferenceGalaxy> {
+    REFERENCE_SAMPLE
+        .iter()
+        .map(|&(name, flat_velocity, log_mass)| ReferenceGalaxy {
+            name,
+            flat_velocity,
+            baryonic_mass: 10.0_f64.powf(log_mass - 10.0),
+        })
+        .collect()
+}
+
+// Normalization A of the relation fitted to the reference sample in 10^10 M☉ / (km/s)⁴,
+// the mean of log M_b - 4 log v_f (about 52 M☉ / (km/s)⁴, McGaugh 2012 finds 47)
+pub fn normalization() -> f64 {
+    let sample = reference_sample();
+    let mean = sample
+        .iter()
+        .map(|galaxy| (galaxy.baryonic_mass / galaxy.flat_velocity.powi(SLOPE)).log10())
+        .sum::<f64>()
+        / sample.len() as f64;
+    10.0_f64.powf(mean)
+}
+
+// Baryonic mass of the relation at a flat velocity (km/s), in 10^10 M☉
+pub fn relation_mass(flat_velocity: f64) -> f64 {
+    normalization() * flat_velocity.powi(SLOPE)
+}
+
+// Total mass of the disk in 10^10 M☉
+pub fn baryonic_mass(disk: &dyn MassComponent, scale: f64) -> f64 {
+    disk.mass(TOTAL_MASS_SCALES * scale)
+}
+
+// Mean total velocity over the outer part of the disk, where measured curves are flat
+pub fn flat_velocity(components: &[Box<dyn MassComponent>], disk_scale: f64) -> f64 {
+    let (first, last) = FLAT_RANGE_SCALES;
+    let velocities: Vec<f64> = (0..FLAT_SAMPLES)
+        .map(|i| {
+            let t = first + (last - first) * i as f64 / (FLAT_SAMPLES - 1) as f64;
+            total_velocity(components, t * disk_scale)
+        })
+        .collect();
+    velocities.iter().sum::<f64>() / velocities.len() as f64
+}
+
+// Distance of a galaxy from the relation in dex, positive if it has more mass than expected
+pub fn offset(baryonic_mass: f64, flat_velocity: f64) -> f64 {
+    (baryonic_mass / relation_mass(flat_velocity)).log10()
+}